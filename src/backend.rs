@@ -0,0 +1,73 @@
+use crate::error::{Result, TmuxrsError};
+use crate::snapshot::find_command_line;
+use crate::tmux::TmuxCommand;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs tmux commands on behalf of a `SessionManager`
+///
+/// `ProcessBackend` spawns the tmux binary and is the default.
+/// `fake::FakeBackend` keeps an in-memory model of sessions, windows and panes
+/// so that code built on tmuxrs can be tested without a tmux server.
+/// `plan::DryRun` records everything instead of running it.
+pub trait TmuxBackend {
+    /// Run a command and return its standard output
+    fn execute(&self, command: TmuxCommand) -> Result<String>;
+
+    /// Run a command that takes over the terminal, such as `attach-session`
+    fn execute_interactive(&self, command: TmuxCommand) -> Result<()>;
+
+    /// Run a single lifecycle hook command with `sh -c` from `working_dir`
+    ///
+    /// Hooks run outside tmux, so by default they are run for real; the fake
+    /// and dry-run backends record them instead. A failing command is reported
+    /// as `HookFailed`.
+    fn run_hook(&self, hook: &str, command: &str, working_dir: &Path) -> Result<()> {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+
+        // Like tmuxinator's `cd root`, keep going from the current directory if root is missing
+        if working_dir.is_dir() {
+            cmd.current_dir(working_dir);
+        }
+
+        let status = cmd.status()?;
+        if !status.success() {
+            return Err(TmuxrsError::HookFailed {
+                hook: hook.to_string(),
+                status,
+            });
+        }
+        Ok(())
+    }
+
+    /// The full command line of `program`, which `#{pane_current_command}`
+    /// reports for the pane whose process is `pane_pid`
+    ///
+    /// Like hooks, programs run outside tmux, so by default this reads the
+    /// process list with `ps`. `None` when the program cannot be found.
+    fn command_line(&self, pane_pid: u32, program: &str) -> Option<String> {
+        let output = Command::new("ps")
+            .args(["-A", "-o", "pid=", "-o", "ppid=", "-o", "args="])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        find_command_line(&String::from_utf8_lossy(&output.stdout), pane_pid, program)
+    }
+}
+
+/// Backend that runs every command with the tmux binary
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessBackend;
+
+impl TmuxBackend for ProcessBackend {
+    fn execute(&self, command: TmuxCommand) -> Result<String> {
+        command.execute()
+    }
+
+    fn execute_interactive(&self, command: TmuxCommand) -> Result<()> {
+        command.execute_interactive()
+    }
+}
@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -7,6 +8,11 @@ use clap::{Parser, Subcommand};
     version = "0.1.0"
 )]
 pub struct Args {
+    /// Directory holding the configurations [default: $TMUXRS_CONFIG_DIR,
+    /// $XDG_CONFIG_HOME/tmuxrs or ~/.config/tmuxrs]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -26,14 +32,134 @@ pub enum Command {
         /// Add windows to existing session instead of creating new one
         #[arg(long)]
         append: bool,
+        /// Print the commands that would run instead of running them
+        #[arg(long)]
+        dry_run: bool,
+        /// Output format for --dry-run
+        #[arg(long, value_enum, default_value_t = PlanFormat::Text, requires = "dry_run")]
+        format: PlanFormat,
     },
     /// List available session configurations
-    List,
+    List {
+        /// Exit with an error if any configuration is invalid
+        #[arg(long)]
+        strict: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+    },
     /// Stop a tmux session
     Stop {
         /// Session name to stop
         name: String,
+        /// Print the commands that would run instead of running them
+        #[arg(long)]
+        dry_run: bool,
+        /// Output format for --dry-run
+        #[arg(long, value_enum, default_value_t = PlanFormat::Text, requires = "dry_run")]
+        format: PlanFormat,
     },
+    /// Print the tmux commands that start would run, without running them
+    Debug {
+        /// Session name (optional, detects from directory if not provided)
+        name: Option<String>,
+    },
+    /// Save a running session as a configuration
+    Freeze {
+        /// Session to save; the configuration gets the same name
+        name: String,
+        /// Overwrite an existing configuration
+        #[arg(long)]
+        force: bool,
+        /// Leave pane programs out of the configuration and list them in a comment
+        #[arg(long)]
+        no_commands: bool,
+    },
+    /// Save the live state of every running session that has a configuration
+    Save {
+        /// Also save the contents and history of every pane
+        #[arg(long)]
+        scrollback: bool,
+    },
+    /// Recreate the sessions saved by `save`
+    Restore {
+        /// Recreate windows, layouts and directories without starting the programs that were running
+        #[arg(long)]
+        no_commands: bool,
+    },
+    /// Create a configuration rooted at the current directory and open it in $EDITOR
+    New {
+        /// Name of the new configuration
+        name: String,
+    },
+    /// Open an existing configuration in $EDITOR
+    Edit {
+        /// Name of the configuration to edit
+        name: String,
+    },
+    /// Copy a configuration under a new name
+    Copy {
+        /// Configuration to copy
+        src: String,
+        /// Name of the copy
+        dst: String,
+        /// Overwrite an existing configuration, even if its session is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete a configuration
+    Delete {
+        /// Configuration to delete
+        name: String,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Delete the configuration even if its session is running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Rename a configuration
+    Rename {
+        /// Current name of the configuration
+        old: String,
+        /// New name of the configuration
+        new: String,
+        /// Rename even if a session is running or the new name is taken
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check tmux, the environment and every configuration for problems
+    Doctor,
+    /// List running sessions and whether each has a configuration
+    #[command(visible_alias = "status")]
+    Ps,
+    /// List the git worktrees of a configuration's project and their sessions
+    Worktrees {
+        /// Configuration whose root is in the repository
+        name: String,
+    },
+}
+
+/// How a dry-run plan is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PlanFormat {
+    /// One shell command per line
+    Text,
+    /// A JSON array of steps
+    Json,
+}
+
+/// How `list` prints the configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// A human-readable list, with invalid configurations in a separate section
+    Text,
+    /// A JSON array of configurations
+    Json,
+    /// A YAML list of configurations
+    Yaml,
+    /// One configuration name per line
+    Names,
 }
 
 #[cfg(test)]
@@ -49,6 +175,7 @@ mod tests {
                 attach,
                 no_attach,
                 append,
+                ..
             } => {
                 assert_eq!(name, Some("my-session".to_string()));
                 assert!(attach);
@@ -68,6 +195,7 @@ mod tests {
                 attach,
                 no_attach,
                 append,
+                ..
             } => {
                 assert_eq!(name, None);
                 assert!(attach);
@@ -87,6 +215,7 @@ mod tests {
                 attach,
                 no_attach,
                 append,
+                ..
             } => {
                 assert_eq!(name, None);
                 assert!(attach); // Default value is still true
@@ -106,6 +235,7 @@ mod tests {
                 attach,
                 no_attach,
                 append,
+                ..
             } => {
                 assert_eq!(name, Some("my-session".to_string()));
                 assert!(attach);
@@ -120,24 +250,188 @@ mod tests {
     fn test_parse_list_command() {
         let args = Args::parse_from(["tmuxrs", "list"]);
         match args.command {
-            Command::List => {
-                // List command has no parameters
+            Command::List { strict, format } => {
+                assert!(!strict);
+                assert_eq!(format, ListFormat::Text);
             }
             _ => panic!("Expected List command"),
         }
+
+        let args = Args::parse_from(["tmuxrs", "list", "--strict", "--format", "names"]);
+        match args.command {
+            Command::List { strict, format } => {
+                assert!(strict);
+                assert_eq!(format, ListFormat::Names);
+            }
+            _ => panic!("Expected List command"),
+        }
+        assert!(Args::try_parse_from(["tmuxrs", "list", "--format", "xml"]).is_err());
     }
 
     #[test]
     fn test_parse_stop_command() {
         let args = Args::parse_from(["tmuxrs", "stop", "my-session"]);
         match args.command {
-            Command::Stop { name } => {
+            Command::Stop { name, dry_run, .. } => {
                 assert_eq!(name, "my-session");
+                assert!(!dry_run);
             }
             _ => panic!("Expected Stop command"),
         }
     }
 
+    #[test]
+    fn test_parse_debug_command() {
+        let args = Args::parse_from(["tmuxrs", "debug", "my-session"]);
+        match args.command {
+            Command::Debug { name } => {
+                assert_eq!(name, Some("my-session".to_string()));
+            }
+            _ => panic!("Expected Debug command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "debug"]);
+        match args.command {
+            Command::Debug { name } => {
+                assert_eq!(name, None);
+            }
+            _ => panic!("Expected Debug command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_freeze_command() {
+        let args = Args::parse_from(["tmuxrs", "freeze", "my-session", "--force"]);
+        match args.command {
+            Command::Freeze {
+                name,
+                force,
+                no_commands,
+            } => {
+                assert_eq!(name, "my-session");
+                assert!(force);
+                assert!(!no_commands);
+            }
+            _ => panic!("Expected Freeze command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "freeze", "my-session", "--no-commands"]);
+        assert!(matches!(
+            args.command,
+            Command::Freeze {
+                no_commands: true,
+                ..
+            }
+        ));
+
+        assert!(Args::try_parse_from(["tmuxrs", "freeze"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_save_and_restore_commands() {
+        let args = Args::parse_from(["tmuxrs", "save", "--scrollback"]);
+        match args.command {
+            Command::Save { scrollback } => assert!(scrollback),
+            _ => panic!("Expected Save command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "restore"]);
+        assert!(matches!(
+            args.command,
+            Command::Restore { no_commands: false }
+        ));
+        let args = Args::parse_from(["tmuxrs", "restore", "--no-commands"]);
+        assert!(matches!(
+            args.command,
+            Command::Restore { no_commands: true }
+        ));
+    }
+
+    #[test]
+    fn test_parse_new_and_edit_commands() {
+        let args = Args::parse_from(["tmuxrs", "new", "my-project"]);
+        match args.command {
+            Command::New { name } => assert_eq!(name, "my-project"),
+            _ => panic!("Expected New command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "edit", "my-project"]);
+        match args.command {
+            Command::Edit { name } => assert_eq!(name, "my-project"),
+            _ => panic!("Expected Edit command"),
+        }
+
+        assert!(Args::try_parse_from(["tmuxrs", "edit"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_copy_delete_and_rename_commands() {
+        let args = Args::parse_from(["tmuxrs", "copy", "web", "api", "--force"]);
+        match args.command {
+            Command::Copy { src, dst, force } => {
+                assert_eq!((src.as_str(), dst.as_str()), ("web", "api"));
+                assert!(force);
+            }
+            _ => panic!("Expected Copy command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "delete", "web", "-y"]);
+        match args.command {
+            Command::Delete { name, yes, force } => {
+                assert_eq!(name, "web");
+                assert!(yes);
+                assert!(!force);
+            }
+            _ => panic!("Expected Delete command"),
+        }
+
+        let args = Args::parse_from(["tmuxrs", "rename", "web", "site"]);
+        match args.command {
+            Command::Rename { old, new, force } => {
+                assert_eq!((old.as_str(), new.as_str()), ("web", "site"));
+                assert!(!force);
+            }
+            _ => panic!("Expected Rename command"),
+        }
+
+        assert!(Args::try_parse_from(["tmuxrs", "copy", "web"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_worktrees_command() {
+        let args = Args::parse_from(["tmuxrs", "worktrees", "acme"]);
+        match args.command {
+            Command::Worktrees { name } => assert_eq!(name, "acme"),
+            _ => panic!("Expected Worktrees command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_ps_command_and_alias() {
+        for command in ["ps", "status"] {
+            let args = Args::parse_from(["tmuxrs", command]);
+            assert!(matches!(args.command, Command::Ps));
+        }
+    }
+
+    #[test]
+    fn test_parse_global_config_dir() {
+        let args = Args::parse_from(["tmuxrs", "list", "--config-dir", "/srv/configs"]);
+        assert_eq!(args.config_dir, Some(PathBuf::from("/srv/configs")));
+
+        let args = Args::parse_from(["tmuxrs", "--config-dir", "/srv/configs", "start", "web"]);
+        assert_eq!(args.config_dir, Some(PathBuf::from("/srv/configs")));
+
+        let args = Args::parse_from(["tmuxrs", "list"]);
+        assert_eq!(args.config_dir, None);
+    }
+
+    #[test]
+    fn test_parse_doctor_command() {
+        let args = Args::parse_from(["tmuxrs", "doctor"]);
+        assert!(matches!(args.command, Command::Doctor));
+    }
+
     #[test]
     fn test_parse_start_with_all_flags() {
         let args = Args::parse_from([
@@ -154,6 +448,7 @@ mod tests {
                 attach,
                 no_attach,
                 append,
+                ..
             } => {
                 assert_eq!(name, Some("test-session".to_string()));
                 assert!(attach);
@@ -163,4 +458,41 @@ mod tests {
             _ => panic!("Expected Start command"),
         }
     }
+
+    #[test]
+    fn test_parse_dry_run_flags() {
+        let args = Args::parse_from(["tmuxrs", "start", "my-session", "--dry-run"]);
+        match args.command {
+            Command::Start {
+                dry_run, format, ..
+            } => {
+                assert!(dry_run);
+                assert_eq!(format, PlanFormat::Text);
+            }
+            _ => panic!("Expected Start command"),
+        }
+
+        let args = Args::parse_from([
+            "tmuxrs",
+            "stop",
+            "my-session",
+            "--dry-run",
+            "--format",
+            "json",
+        ]);
+        match args.command {
+            Command::Stop {
+                dry_run, format, ..
+            } => {
+                assert!(dry_run);
+                assert_eq!(format, PlanFormat::Json);
+            }
+            _ => panic!("Expected Stop command"),
+        }
+
+        // --format only makes sense together with --dry-run
+        assert!(
+            Args::try_parse_from(["tmuxrs", "stop", "my-session", "--format", "json"]).is_err()
+        );
+    }
 }
@@ -1,44 +1,364 @@
 use crate::error::{Result, TmuxrsError};
+use crate::git::GitRepository;
+use crate::settings::Settings;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Environment variable that overrides the config directory
+pub const CONFIG_DIR_ENV: &str = "TMUXRS_CONFIG_DIR";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub root: Option<String>,
+    /// Sent to every pane before its own command (e.g. `nvm use`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_window: Option<Hook>,
+    /// Window to select after the session is built, by name or by position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup_window: Option<WindowTarget>,
+    /// Pane to select in the startup window, by its 0-based position in the
+    /// window's `panes`, wherever splits with a `target` put it in tmux
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup_pane: Option<usize>,
+    /// Runs on every start, before anything else
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_start: Option<Hook>,
+    /// Runs only when the session does not exist yet, before it is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_first_start: Option<Hook>,
+    /// Runs when starting a session that is already running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_restart: Option<Hook>,
+    /// Runs when a client detaches from the session (installed as a tmux hook)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_exit: Option<Hook>,
+    /// Runs after the session has been stopped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_stop: Option<Hook>,
     pub windows: Vec<WindowConfig>,
 }
 
+/// Reference to a window of the config, either by name or by 0-based position
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum WindowTarget {
+    Index(usize),
+    Name(String),
+}
+
+/// A lifecycle hook: a single shell command or a list of commands run in order
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Hook {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Hook {
+    /// The shell commands that make up this hook, skipping blank entries
+    pub fn commands(&self) -> Vec<&str> {
+        let commands: Vec<&str> = match self {
+            Hook::Single(command) => vec![command.as_str()],
+            Hook::Multiple(commands) => commands.iter().map(String::as_str).collect(),
+        };
+        commands
+            .into_iter()
+            .filter(|command| !command.trim().is_empty())
+            .collect()
+    }
+}
+
+/// A single entry of the `windows` list
+///
+/// Mappings are kept in an `IndexMap` so that windows are created in the order
+/// they appear in the YAML file.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum WindowConfig {
     Simple(String),
     Complex {
         #[serde(flatten)]
-        window: HashMap<String, String>,
+        window: IndexMap<String, String>,
     },
     WithLayout {
         #[serde(flatten)]
-        window: HashMap<String, WindowLayout>,
+        window: IndexMap<String, WindowLayout>,
     },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WindowLayout {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<String>,
-    pub panes: Vec<String>,
+    /// Working directory for the window, relative to the session root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Sent to every pane of this window, after `pre_window` and before the pane command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre: Option<Hook>,
+    pub panes: Vec<PaneConfig>,
+}
+
+/// A pane: either just its command, or a mapping with extra options
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PaneConfig {
+    Command(String),
+    Detailed(PaneOptions),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaneOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Working directory for the pane, relative to the session root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// How the pane is split off from its target pane (default: horizontal)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<SplitDirection>,
+    /// Size of the new pane, as a percentage (`"30%"`) or a number of cells (`20`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<PaneSize>,
+    /// Pane to split, by 0-based position in `panes` (default: the previous pane)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<usize>,
+}
+
+/// Direction in which a new pane is split off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    /// Side by side (`split-window -h`)
+    #[default]
+    Horizontal,
+    /// Above and below (`split-window -v`)
+    Vertical,
+}
+
+/// Size of a new pane, written as `"30%"` or as a number of cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "PaneSizeValue", into = "PaneSizeValue")]
+pub enum PaneSize {
+    Percentage(u8),
+    Cells(u32),
+}
+
+/// How a `PaneSize` appears in YAML
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum PaneSizeValue {
+    Cells(u32),
+    Text(String),
+}
+
+impl TryFrom<PaneSizeValue> for PaneSize {
+    type Error = String;
+
+    fn try_from(value: PaneSizeValue) -> std::result::Result<Self, Self::Error> {
+        let text = match value {
+            PaneSizeValue::Cells(cells) => return Ok(PaneSize::Cells(cells)),
+            PaneSizeValue::Text(text) => text,
+        };
+
+        let parsed = match text.trim().strip_suffix('%') {
+            Some(percentage) => percentage
+                .trim()
+                .parse()
+                .ok()
+                .filter(|percentage| (1..=99).contains(percentage))
+                .map(PaneSize::Percentage),
+            None => text.trim().parse().ok().map(PaneSize::Cells),
+        };
+        parsed.ok_or_else(|| {
+            format!("invalid pane size '{text}', expected a percentage like \"30%\" or a number of cells")
+        })
+    }
+}
+
+impl From<PaneSize> for PaneSizeValue {
+    fn from(size: PaneSize) -> Self {
+        match size {
+            PaneSize::Cells(cells) => PaneSizeValue::Cells(cells),
+            PaneSize::Percentage(_) => PaneSizeValue::Text(size.to_string()),
+        }
+    }
+}
+
+/// Renders the size as tmux's `-l` expects it, e.g. `30%` or `20`
+impl std::fmt::Display for PaneSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaneSize::Percentage(percentage) => write!(f, "{percentage}%"),
+            PaneSize::Cells(cells) => write!(f, "{cells}"),
+        }
+    }
+}
+
+/// Expand tilde (~) and environment variables in a path from a config or settings file
+///
+/// Falls back to tilde expansion alone when a variable is not set.
+pub fn expand_path(path: &str) -> PathBuf {
+    match shellexpand::full(path) {
+        Ok(expanded) => PathBuf::from(expanded.as_ref()),
+        Err(_) => PathBuf::from(shellexpand::tilde(path).as_ref()),
+    }
+}
+
+/// A string as a YAML scalar, quoted only if it needs to be
+fn yaml_scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|yaml| yaml.trim_end().to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+/// Why a config file could not be parsed, and where
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ConfigParseError {
+    pub path: PathBuf,
+    /// 1-based line of the error, when known
+    pub line: Option<usize>,
+    /// 1-based column of the error, when known
+    pub column: Option<usize>,
+    pub message: String,
+    /// The text of the offending line
+    pub source_line: Option<String>,
+}
+
+impl ConfigParseError {
+    pub(crate) fn new(path: &Path, content: &str, error: &serde_yaml::Error) -> Self {
+        let location = error.location();
+        let line = location.as_ref().map(|location| location.line());
+        let column = location.as_ref().map(|location| location.column());
+
+        // The position is shown separately, so drop it from serde_yaml's message
+        let mut message = error.to_string();
+        if let (Some(line), Some(column)) = (line, column) {
+            message = message.replacen(&format!(" at line {line} column {column}"), "", 1);
+        }
+
+        let source_line = line
+            .and_then(|line| content.lines().nth(line.checked_sub(1)?))
+            .map(str::to_string);
+
+        Self {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message,
+            source_line,
+        }
+    }
+
+    /// A file that could not be read at all
+    fn unreadable(path: &Path, error: &std::io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line: None,
+            column: None,
+            message: error.to_string(),
+            source_line: None,
+        }
+    }
+}
+
+/// A file of a config directory: its config, or why it could not be loaded
+#[derive(Debug)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub config: std::result::Result<Config, ConfigParseError>,
+    /// The file that is used instead of this one, when an earlier directory
+    /// has a config of the same name
+    pub shadowed_by: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    /// Read and parse a config file, keeping any error instead of returning it
+    pub fn load(path: &Path) -> Self {
+        let config = std::fs::read_to_string(path)
+            .map_err(|error| ConfigParseError::unreadable(path, &error))
+            .and_then(|content| {
+                serde_yaml::from_str(&content)
+                    .map_err(|error| ConfigParseError::new(path, &content, &error))
+            });
+        Self {
+            path: path.to_path_buf(),
+            config,
+            shadowed_by: None,
+        }
+    }
+}
+
+/// Renders as `path:line:column: message`, followed by the offending line and a caret
+impl std::fmt::Display for ConfigParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, ": {}", self.message)?;
+
+        if let (Some(line), Some(source_line)) = (self.line, &self.source_line) {
+            let gutter = " ".repeat(line.to_string().len());
+            write!(f, "\n{gutter} |\n{line} | {source_line}")?;
+            if let Some(column) = self.column {
+                write!(f, "\n{gutter} | {}^", " ".repeat(column.saturating_sub(1)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PaneConfig {
+    /// The command to run in the pane (empty for a plain shell)
+    pub fn command(&self) -> &str {
+        match self {
+            PaneConfig::Command(command) => command,
+            PaneConfig::Detailed(options) => options.command.as_deref().unwrap_or(""),
+        }
+    }
+
+    /// The pane's own root override, if any
+    pub fn root(&self) -> Option<&str> {
+        match self {
+            PaneConfig::Command(_) => None,
+            PaneConfig::Detailed(options) => options.root.as_deref(),
+        }
+    }
+
+    /// The extra options of a pane given as a mapping
+    pub fn options(&self) -> Option<&PaneOptions> {
+        match self {
+            PaneConfig::Command(_) => None,
+            PaneConfig::Detailed(options) => Some(options),
+        }
+    }
 }
 
 impl Config {
-    /// Detect session name from directory basename
-    /// If path is None, uses current directory
+    /// Detect session name from the enclosing git repository, or else the directory basename
+    ///
+    /// Linked worktrees are named after the repository they belong to. If path
+    /// is None, uses current directory.
     #[allow(dead_code)]
     pub fn detect_session_name(path: Option<&Path>) -> Result<String> {
         let dir = match path {
             Some(p) => p.to_path_buf(),
             None => std::env::current_dir()?,
         };
+        if let Some(name) = GitRepository::discover(&dir).and_then(|repo| repo.name()) {
+            return Ok(name);
+        }
+
         let basename = dir
             .file_name()
             .and_then(|name| name.to_str())
@@ -48,15 +368,103 @@ impl Config {
         Ok(basename.to_string())
     }
 
-    /// Get config file path for a session name
-    #[allow(dead_code)]
-    pub fn get_config_file_path(session_name: &str) -> Result<PathBuf> {
-        let home_dir = dirs::home_dir().ok_or_else(|| {
+    /// The directory holding config files
+    ///
+    /// `$TMUXRS_CONFIG_DIR` when set, then `$XDG_CONFIG_HOME/tmuxrs`, then
+    /// `~/.config/tmuxrs`. Every lookup without an explicit `config_dir` goes
+    /// through here.
+    pub fn config_dir() -> Result<PathBuf> {
+        Self::resolve_config_dir(
+            std::env::var_os(CONFIG_DIR_ENV),
+            std::env::var_os("XDG_CONFIG_HOME"),
+            dirs::home_dir(),
+        )
+    }
+
+    fn resolve_config_dir(
+        override_dir: Option<OsString>,
+        config_home: Option<OsString>,
+        home_dir: Option<PathBuf>,
+    ) -> Result<PathBuf> {
+        if let Some(dir) = override_dir.filter(|dir| !dir.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
+
+        // Relative values are invalid per the XDG spec and are ignored
+        if let Some(config_home) = config_home
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+        {
+            return Ok(config_home.join("tmuxrs"));
+        }
+
+        let home_dir = home_dir.ok_or_else(|| {
             TmuxrsError::ConfigNotFound("Could not find home directory".to_string())
         })?;
+        Ok(home_dir.join(".config").join("tmuxrs"))
+    }
+
+    /// Every directory searched for configs, in order of precedence
+    ///
+    /// The tmuxrs directory from `config_dir` comes first, then tmuxinator's
+    /// `~/.tmuxinator` and `$XDG_CONFIG_HOME/tmuxinator` (or
+    /// `~/.config/tmuxinator`), then the `config_dirs` of the settings file.
+    pub fn config_dirs() -> Result<Vec<PathBuf>> {
+        let config_dir = Self::config_dir()?;
+        let extra_dirs = Settings::load(&config_dir)?.extra_config_dirs();
+        Ok(Self::search_path(
+            config_dir,
+            std::env::var_os("XDG_CONFIG_HOME"),
+            dirs::home_dir(),
+            extra_dirs,
+        ))
+    }
 
-        let config_dir = home_dir.join(".config").join("tmuxrs");
-        let config_file = config_dir.join(format!("{session_name}.yml"));
+    fn search_path(
+        config_dir: PathBuf,
+        config_home: Option<OsString>,
+        home_dir: Option<PathBuf>,
+        extra_dirs: Vec<PathBuf>,
+    ) -> Vec<PathBuf> {
+        let config_home = config_home
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home_dir.as_ref().map(|home| home.join(".config")));
+
+        let mut search_path = vec![config_dir];
+        search_path.extend(home_dir.map(|home| home.join(".tmuxinator")));
+        search_path.extend(config_home.map(|dir| dir.join("tmuxinator")));
+        search_path.extend(extra_dirs);
+
+        // A directory listed twice would only shadow itself
+        let mut seen = Vec::new();
+        search_path.retain(|dir| {
+            let first = !seen.contains(dir);
+            seen.push(dir.clone());
+            first
+        });
+        search_path
+    }
+
+    /// The config file for a session in one directory, preferring `.yml` over `.yaml`
+    pub fn config_file_in(config_dir: &Path, session_name: &str) -> Option<PathBuf> {
+        ["yml", "yaml"]
+            .iter()
+            .map(|ext| config_dir.join(format!("{session_name}.{ext}")))
+            .find(|path| path.is_file())
+    }
+
+    /// The config file for a session in the first directory of `config_dirs` that has one
+    pub fn find_config_file(session_name: &str) -> Result<Option<PathBuf>> {
+        Ok(Self::config_dirs()?
+            .iter()
+            .find_map(|dir| Self::config_file_in(dir, session_name)))
+    }
+
+    /// Get config file path for a session name
+    #[allow(dead_code)]
+    pub fn get_config_file_path(session_name: &str) -> Result<PathBuf> {
+        let config_file = Self::config_dir()?.join(format!("{session_name}.yml"));
 
         Ok(config_file)
     }
@@ -64,32 +472,148 @@ impl Config {
     /// Load configuration for a session
     #[allow(dead_code)]
     pub fn load(session_name: &str) -> Result<Config> {
-        let config_path = Self::get_config_file_path(session_name)?;
-
-        if !config_path.exists() {
+        let Some(config_path) = Self::find_config_file(session_name)? else {
             return Err(TmuxrsError::ConfigNotFound(format!(
                 "Configuration file not found: {}",
-                config_path.display()
+                Self::get_config_file_path(session_name)?.display()
             )));
-        }
+        };
 
         Self::parse_file(&config_path)
     }
 
     /// Parse configuration from a YAML file
+    ///
+    /// Errors are reported as `ConfigParseError`, with the position and text of
+    /// the offending line.
     #[allow(dead_code)]
     pub fn parse_file(file_path: &Path) -> Result<Config> {
         let content = std::fs::read_to_string(file_path)?;
-        let config: Config = serde_yaml::from_str(&content)?;
+        let config: Config = serde_yaml::from_str(&content)
+            .map_err(|error| ConfigParseError::new(file_path, &content, &error))?;
         Ok(config)
     }
+
+    /// A commented starting point for a new configuration
+    #[allow(dead_code)]
+    pub fn template(name: &str, root: &Path) -> String {
+        format!(
+            r#"# tmuxrs configuration for {name}
+# Start it with `tmuxrs start {name}`, or with `tmuxrs start` from its root directory.
+
+name: {name_value}
+root: {root_value}
+
+# Sent to every pane before its own command
+# pre_window: nvm use
+
+# Window to select once the session is built, by name or position
+# startup_window: editor
+
+# Lifecycle hooks, run from the root directory
+# on_project_start: docker compose up -d
+# on_project_stop: docker compose down
+
+windows:
+  # A window running a single command
+  - editor: vim
+  # A window split into panes; "" is a plain shell
+  - shell:
+      layout: even-horizontal
+      panes:
+        - git status
+        - ""
+"#,
+            name_value = yaml_scalar(name),
+            root_value = yaml_scalar(&Self::root_value(root)),
+        )
+    }
+
+    /// Set the top-level `name` of a config file's contents, keeping its comments and layout
+    pub fn with_name(content: &str, name: &str) -> String {
+        let name_line = format!("name: {}", yaml_scalar(name));
+        let mut lines: Vec<&str> = content.lines().collect();
+        match lines.iter().position(|line| line.starts_with("name:")) {
+            Some(index) => lines[index] = &name_line,
+            None => lines.insert(0, &name_line),
+        }
+
+        let mut output = lines.join("\n");
+        if content.ends_with('\n') || content.is_empty() {
+            output.push('\n');
+        }
+        output
+    }
+
+    /// A `root` value for a directory, written as `~/...` when it is inside the home directory
+    #[allow(dead_code)]
+    pub fn root_value(dir: &Path) -> String {
+        match dirs::home_dir().and_then(|home| dir.strip_prefix(home).ok().map(Path::to_path_buf)) {
+            Some(relative) if relative.as_os_str().is_empty() => "~".to_string(),
+            Some(relative) => format!("~/{}", relative.display()),
+            None => dir.display().to_string(),
+        }
+    }
+
+    /// Write the configuration to a YAML file, creating its directory if needed
+    #[allow(dead_code)]
+    pub fn write_file(&self, file_path: &Path) -> Result<()> {
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file_path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::test_support::add_worktree;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_expand_path_home_directory() {
+        // Test tilde expansion
+        let path = expand_path("~/projects");
+        assert!(path.is_absolute());
+        assert!(!path.to_string_lossy().contains('~'));
+    }
+
+    #[test]
+    fn test_expand_path_environment_variable() {
+        // Set a test environment variable
+        std::env::set_var("TEST_PATH", "/tmp/test");
+
+        let path = expand_path("$TEST_PATH/project");
+        assert_eq!(path.to_string_lossy(), "/tmp/test/project");
+
+        // Clean up
+        std::env::remove_var("TEST_PATH");
+    }
+
+    #[test]
+    fn test_expand_path_no_expansion_needed() {
+        // Test absolute path
+        let path = expand_path("/usr/local/bin");
+        assert_eq!(path.to_string_lossy(), "/usr/local/bin");
+    }
+
+    #[test]
+    fn test_expand_path_combined() {
+        // Test combined tilde and env var
+        std::env::set_var("TEST_DIR", "mydir");
+
+        let path = expand_path("~/$TEST_DIR/project");
+        assert!(path.is_absolute());
+        assert!(path.to_string_lossy().contains("mydir/project"));
+        assert!(!path.to_string_lossy().contains('~'));
+        assert!(!path.to_string_lossy().contains("$TEST_DIR"));
+
+        // Clean up
+        std::env::remove_var("TEST_DIR");
+    }
+
     #[test]
     fn test_detect_session_name_from_directory() {
         // Test directory basename detection
@@ -105,11 +629,79 @@ mod tests {
     fn test_get_config_file_path() {
         let config_path = Config::get_config_file_path("test-session").unwrap();
 
-        // Should be ~/.config/tmuxrs/test-session.yml
-        assert!(config_path.to_string_lossy().contains(".config/tmuxrs"));
+        // Should be <config dir>/test-session.yml
+        assert_eq!(
+            config_path.parent(),
+            Some(Config::config_dir().unwrap().as_path())
+        );
         assert!(config_path.to_string_lossy().ends_with("test-session.yml"));
     }
 
+    #[test]
+    fn test_search_path_order() {
+        let home = PathBuf::from("/home/me");
+        let search_path = Config::search_path(
+            home.join(".config/tmuxrs"),
+            None,
+            Some(home.clone()),
+            vec![PathBuf::from("/srv/shared"), home.join(".tmuxinator")],
+        );
+        assert_eq!(
+            search_path,
+            vec![
+                home.join(".config/tmuxrs"),
+                home.join(".tmuxinator"),
+                home.join(".config/tmuxinator"),
+                PathBuf::from("/srv/shared"),
+            ]
+        );
+
+        let search_path = Config::search_path(
+            PathBuf::from("/xdg/tmuxrs"),
+            Some(OsString::from("/xdg")),
+            Some(home.clone()),
+            Vec::new(),
+        );
+        assert_eq!(search_path[2], PathBuf::from("/xdg/tmuxinator"));
+    }
+
+    #[test]
+    fn test_config_file_in_prefers_yml() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(Config::config_file_in(temp_dir.path(), "web"), None);
+
+        std::fs::write(temp_dir.path().join("web.yaml"), "").unwrap();
+        assert_eq!(
+            Config::config_file_in(temp_dir.path(), "web"),
+            Some(temp_dir.path().join("web.yaml"))
+        );
+        std::fs::write(temp_dir.path().join("web.yml"), "").unwrap();
+        assert_eq!(
+            Config::config_file_in(temp_dir.path(), "web"),
+            Some(temp_dir.path().join("web.yml"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_config_dir_precedence() {
+        let os = |value: &str| Some(OsString::from(value));
+        let home = Some(PathBuf::from("/home/me"));
+
+        let resolve = |override_dir, config_home| {
+            Config::resolve_config_dir(override_dir, config_home, home.clone()).unwrap()
+        };
+        assert_eq!(
+            resolve(os("/srv/configs"), os("/xdg")),
+            PathBuf::from("/srv/configs")
+        );
+        assert_eq!(resolve(None, os("/xdg")), PathBuf::from("/xdg/tmuxrs"));
+        assert_eq!(
+            resolve(os(""), os("relative/xdg")),
+            PathBuf::from("/home/me/.config/tmuxrs")
+        );
+        assert!(Config::resolve_config_dir(None, None, None).is_err());
+    }
+
     #[test]
     fn test_load_config_file_not_found() {
         let result = Config::load("nonexistent-session");
@@ -143,6 +735,297 @@ windows:
         assert_eq!(config.windows.len(), 2);
     }
 
+    #[test]
+    fn test_parse_preserves_window_order() {
+        let yaml_content = r#"
+name: ordered
+windows:
+  - editor: vim
+    server: rails server
+    logs: tail -f log/development.log
+    console: rails console
+  - zeta:
+      panes:
+        - htop
+    alpha:
+      panes:
+        - top
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml_content).unwrap();
+
+        match &config.windows[0] {
+            WindowConfig::Complex { window } => {
+                let names: Vec<&str> = window.keys().map(String::as_str).collect();
+                assert_eq!(names, vec!["editor", "server", "logs", "console"]);
+            }
+            other => panic!("Expected Complex window, got {other:?}"),
+        }
+
+        match &config.windows[1] {
+            WindowConfig::WithLayout { window } => {
+                let names: Vec<&str> = window.keys().map(String::as_str).collect();
+                assert_eq!(names, vec!["zeta", "alpha"]);
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_lifecycle_hooks() {
+        let yaml_content = r#"
+name: hooks
+on_project_start: echo start
+on_project_first_start:
+  - docker compose up -d
+  - ./scripts/wait-for-db
+on_project_stop: docker compose down
+windows:
+  - editor: vim
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml_content).unwrap();
+
+        assert_eq!(
+            config.on_project_start.as_ref().unwrap().commands(),
+            vec!["echo start"]
+        );
+        assert_eq!(
+            config.on_project_first_start.as_ref().unwrap().commands(),
+            vec!["docker compose up -d", "./scripts/wait-for-db"]
+        );
+        assert_eq!(
+            config.on_project_stop.as_ref().unwrap().commands(),
+            vec!["docker compose down"]
+        );
+        assert!(config.on_project_restart.is_none());
+        assert!(config.on_project_exit.is_none());
+    }
+
+    #[test]
+    fn test_parse_pre_window_and_window_pre() {
+        let yaml_content = r#"
+name: pre
+pre_window: nvm use
+windows:
+  - api:
+      pre:
+        - source .venv/bin/activate
+        - export DEBUG=1
+      panes:
+        - python manage.py runserver
+        - pytest -f
+  - editor: vim
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml_content).unwrap();
+        assert_eq!(
+            config.pre_window.as_ref().unwrap().commands(),
+            vec!["nvm use"]
+        );
+
+        match &config.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                let api = &window["api"];
+                assert_eq!(
+                    api.pre.as_ref().unwrap().commands(),
+                    vec!["source .venv/bin/activate", "export DEBUG=1"]
+                );
+                assert!(api.layout.is_none());
+                assert_eq!(api.panes.len(), 2);
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_window_and_pane_roots() {
+        let yaml_content = r#"
+name: monorepo
+root: ~/work/monorepo
+windows:
+  - frontend:
+      root: frontend
+      panes:
+        - npm run dev
+        - command: npm test -- --watch
+          root: frontend/packages/ui
+        - root: /var/log
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml_content).unwrap();
+
+        match &config.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                let frontend = &window["frontend"];
+                assert_eq!(frontend.root.as_deref(), Some("frontend"));
+
+                let panes = &frontend.panes;
+                assert_eq!(panes[0].command(), "npm run dev");
+                assert_eq!(panes[0].root(), None);
+                assert_eq!(panes[1].command(), "npm test -- --watch");
+                assert_eq!(panes[1].root(), Some("frontend/packages/ui"));
+                assert_eq!(panes[2].command(), "");
+                assert_eq!(panes[2].root(), Some("/var/log"));
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_pane_split_options() {
+        let yaml_content = r#"
+name: splits
+windows:
+  - main:
+      panes:
+        - vim
+        - command: cargo watch
+          split: vertical
+          size: 30%
+        - command: htop
+          size: 20
+          target: 0
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml_content).unwrap();
+
+        match &config.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                let panes = &window["main"].panes;
+                assert!(panes[0].options().is_none());
+
+                let watch = panes[1].options().unwrap();
+                assert_eq!(watch.split, Some(SplitDirection::Vertical));
+                assert_eq!(watch.size, Some(PaneSize::Percentage(30)));
+                assert_eq!(watch.target, None);
+
+                let htop = panes[2].options().unwrap();
+                assert_eq!(htop.split, None);
+                assert_eq!(htop.size, Some(PaneSize::Cells(20)));
+                assert_eq!(htop.target, Some(0));
+                assert_eq!(htop.size.unwrap().to_string(), "20");
+                assert_eq!(watch.size.unwrap().to_string(), "30%");
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+
+        for bad in ["size: 150%", "size: half", "split: diagonal"] {
+            let yaml = format!("name: bad\nwindows:\n  - main:\n      panes:\n        - {bad}\n");
+            let result: std::result::Result<Config, _> = serde_yaml::from_str(&yaml);
+            assert!(result.is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_parse_pane_rejects_unknown_fields() {
+        let yaml_content = r#"
+name: typo
+windows:
+  - main:
+      panes:
+        - comand: vim
+"#;
+
+        let result: std::result::Result<Config, _> = serde_yaml::from_str(yaml_content);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_startup_window_and_pane() {
+        let by_name: Config = serde_yaml::from_str(
+            r#"
+name: startup
+startup_window: editor
+startup_pane: 1
+windows:
+  - editor: vim
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            by_name.startup_window,
+            Some(WindowTarget::Name(ref name)) if name == "editor"
+        ));
+        assert_eq!(by_name.startup_pane, Some(1));
+
+        let by_index: Config = serde_yaml::from_str(
+            r#"
+name: startup
+startup_window: 2
+windows:
+  - editor: vim
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            by_index.startup_window,
+            Some(WindowTarget::Index(2))
+        ));
+        assert_eq!(by_index.startup_pane, None);
+    }
+
+    #[test]
+    fn test_parse_file_reports_offending_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("broken.yml");
+        std::fs::write(
+            &config_file,
+            "name: broken\nwindows:\n  - editor:\n      panes: vim\n",
+        )
+        .unwrap();
+
+        let error = match Config::parse_file(&config_file) {
+            Err(TmuxrsError::ConfigParse(error)) => error,
+            other => panic!("Expected ConfigParse error, got {other:?}"),
+        };
+        assert_eq!(error.path, config_file);
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.source_line.as_deref(), Some("  - editor:"));
+        assert!(!error.message.contains("at line"), "{}", error.message);
+
+        let display = error.to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert!(lines[0].starts_with(&format!("{}:3:", config_file.display())));
+        assert_eq!(lines[2], "3 |   - editor:");
+        assert!(lines[3].ends_with('^'));
+    }
+
+    #[test]
+    fn test_template_is_a_valid_config() {
+        let root = dirs::home_dir().unwrap().join("code").join("my app");
+        let template = Config::template("my-app", &root);
+
+        let config: Config = serde_yaml::from_str(&template).unwrap();
+        assert_eq!(config.name, "my-app");
+        assert_eq!(config.root.as_deref(), Some("~/code/my app"));
+        assert_eq!(config.windows.len(), 2);
+        assert!(template.contains("# pre_window:"));
+
+        assert_eq!(Config::root_value(Path::new("/srv/app")), "/srv/app");
+    }
+
+    #[test]
+    fn test_with_name_keeps_comments() {
+        let content = "# My project\nname: old # inline\nroot: ~/old\nwindows:\n  - name: vim\n";
+        assert_eq!(
+            Config::with_name(content, "new"),
+            "# My project\nname: new\nroot: ~/old\nwindows:\n  - name: vim\n"
+        );
+
+        // A config without a name gets one, and odd names are quoted
+        assert_eq!(
+            Config::with_name("windows: []", "123"),
+            "name: '123'\nwindows: []"
+        );
+    }
+
+    #[test]
+    fn test_hook_commands_skip_blank_entries() {
+        let hook = Hook::Multiple(vec!["echo one".to_string(), "  ".to_string()]);
+        assert_eq!(hook.commands(), vec!["echo one"]);
+    }
+
     #[test]
     fn test_configuration_discovery_integration() {
         // This test verifies the complete configuration discovery flow:
@@ -208,13 +1091,56 @@ windows:
         }
     }
 
+    #[test]
+    fn test_detect_session_name_from_git_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("myproj");
+        std::fs::create_dir_all(project.join(".git")).unwrap();
+        std::fs::create_dir_all(project.join("src").join("api")).unwrap();
+
+        let detected = Config::detect_session_name(Some(&project.join("src").join("api"))).unwrap();
+        assert_eq!(detected, "myproj");
+    }
+
+    #[test]
+    fn test_detect_session_name_from_git_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let main = temp_dir.path().join("myproj");
+        std::fs::create_dir_all(main.join(".git").join("modules").join("lib")).unwrap();
+
+        // A linked worktree is named after the repository it belongs to
+        let worktree = temp_dir.path().join("myproj-feature");
+        add_worktree(&main.join(".git"), &worktree).unwrap();
+        std::fs::create_dir_all(worktree.join("src")).unwrap();
+        let detected = Config::detect_session_name(Some(&worktree.join("src"))).unwrap();
+        assert_eq!(detected, "myproj");
+
+        // A submodule is a repository of its own
+        let submodule = main.join("vendor").join("lib");
+        std::fs::create_dir_all(submodule.join("src")).unwrap();
+        std::fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/lib\n").unwrap();
+        let detected = Config::detect_session_name(Some(&submodule.join("src"))).unwrap();
+        assert_eq!(detected, "lib");
+    }
+
     #[test]
     fn test_detect_session_name_current_directory() {
-        // Test that passing None uses current directory
+        // Passing None resolves the current directory
         let current_dir = std::env::current_dir().unwrap();
-        let expected_name = current_dir.file_name().unwrap().to_str().unwrap();
+        assert_eq!(
+            Config::detect_session_name(None).unwrap(),
+            Config::detect_session_name(Some(&current_dir)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detect_session_name_outside_git_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("scratch").join("notes");
+        std::fs::create_dir_all(&plain).unwrap();
+        assert_eq!(GitRepository::discover(&plain), None);
 
-        let detected = Config::detect_session_name(None).unwrap();
-        assert_eq!(detected, expected_name);
+        let detected = Config::detect_session_name(Some(&plain)).unwrap();
+        assert_eq!(detected, "notes");
     }
 }
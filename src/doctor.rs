@@ -0,0 +1,410 @@
+use crate::config::ConfigFile;
+use crate::session::SessionManager;
+use crate::settings::Settings;
+use std::ffi::OsStr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Outcome of a single `tmuxrs doctor` check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// Works, but probably not the way the user expects
+    Warning,
+    /// Stops tmuxrs, or a config, from working
+    Error,
+}
+
+/// A finding of `tmuxrs doctor` about one part of the environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, e.g. `tmux` or a config file name
+    pub subject: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(subject: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Renders the check as `ok      subject: detail`, indenting multi-line details
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self.status {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Error => "error",
+        };
+        let detail = self.detail.replace('\n', "\n          ");
+        write!(f, "{status:<8}{}: {detail}", self.subject)
+    }
+}
+
+/// Check the environment tmuxrs runs in and every config in `config_dirs`
+///
+/// The first directory is the tmuxrs one, which should exist; the others are
+/// only checked when they do.
+pub fn run_checks(config_dirs: &[PathBuf]) -> Vec<Check> {
+    let env = |name: &str| std::env::var(name).ok();
+
+    let mut checks = vec![
+        check_tmux(std::env::var_os("PATH").as_deref()),
+        check_shell(env("SHELL")),
+        check_editor(env("EDITOR"), env("VISUAL")),
+    ];
+    if let Some(config_dir) = config_dirs.first() {
+        checks.extend(check_settings(config_dir));
+    }
+    checks.extend(check_config_dirs(config_dirs));
+    checks
+}
+
+/// Whether any check failed outright
+pub fn has_errors(checks: &[Check]) -> bool {
+    checks
+        .iter()
+        .any(|check| check.status == CheckStatus::Error)
+}
+
+/// Find `tmux` in `path` and ask it for its version
+fn check_tmux(path: Option<&OsStr>) -> Check {
+    let Some(tmux) = path.and_then(|path| find_executable("tmux", path)) else {
+        return Check::new("tmux", CheckStatus::Error, "not found on PATH");
+    };
+
+    match Command::new(&tmux).arg("-V").output() {
+        Ok(output) if output.status.success() => Check::new(
+            "tmux",
+            CheckStatus::Ok,
+            format!(
+                "{} ({})",
+                String::from_utf8_lossy(&output.stdout).trim(),
+                tmux.display()
+            ),
+        ),
+        Ok(output) => Check::new(
+            "tmux",
+            CheckStatus::Error,
+            format!(
+                "{} -V failed: {}",
+                tmux.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ),
+        Err(err) => Check::new(
+            "tmux",
+            CheckStatus::Error,
+            format!("could not run {}: {err}", tmux.display()),
+        ),
+    }
+}
+
+/// The first executable file called `name` in a `PATH`-style list of directories
+fn find_executable(name: &str, path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| {
+            candidate.metadata().is_ok_and(|metadata| {
+                metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+            })
+        })
+}
+
+/// Hooks run through `sh`, but tmux starts panes with `$SHELL`
+fn check_shell(shell: Option<String>) -> Check {
+    match shell.filter(|shell| !shell.trim().is_empty()) {
+        Some(shell) => Check::new("$SHELL", CheckStatus::Ok, shell),
+        None => Check::new(
+            "$SHELL",
+            CheckStatus::Warning,
+            "not set, tmux will use its default-shell option",
+        ),
+    }
+}
+
+/// `new` and `edit` open `$VISUAL` or `$EDITOR`, falling back to vi
+fn check_editor(editor: Option<String>, visual: Option<String>) -> Check {
+    let set = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+    match (set(editor), set(visual)) {
+        (editor, Some(visual)) => Check::new(
+            "$EDITOR",
+            CheckStatus::Ok,
+            match editor {
+                Some(editor) => format!("{editor}, but $VISUAL ({visual}) is used"),
+                None => format!("not set, $VISUAL ({visual}) is used"),
+            },
+        ),
+        (Some(editor), None) => Check::new("$EDITOR", CheckStatus::Ok, editor),
+        (None, None) => Check::new(
+            "$EDITOR",
+            CheckStatus::Warning,
+            "not set, `tmuxrs new` and `tmuxrs edit` will use vi",
+        ),
+    }
+}
+
+/// Parse the settings file of the tmuxrs config directory, if there is one
+fn check_settings(config_dir: &Path) -> Option<Check> {
+    let path = Settings::path(config_dir);
+    if !path.exists() {
+        return None;
+    }
+
+    Some(match Settings::load(config_dir) {
+        Ok(settings) => Check::new(
+            "settings",
+            CheckStatus::Ok,
+            format!(
+                "{} ({} extra config dirs)",
+                path.display(),
+                settings.config_dirs.len()
+            ),
+        ),
+        Err(err) => Check::new("settings", CheckStatus::Error, err.to_string()),
+    })
+}
+
+/// Check every config directory and the configs `SessionManager::load_configs`
+/// finds in them
+///
+/// Only the first directory, the tmuxrs one, is expected to exist. Files that
+/// `tmuxrs list` does not show, or that `start` does not use, are reported with
+/// the reason.
+fn check_config_dirs(config_dirs: &[PathBuf]) -> Vec<Check> {
+    let config_dirs: Vec<&PathBuf> = config_dirs
+        .iter()
+        .enumerate()
+        .filter(|(position, dir)| *position == 0 || dir.exists())
+        .map(|(_, dir)| dir)
+        .collect();
+    let problems: Vec<Option<Check>> = config_dirs.iter().map(|dir| check_dir(dir)).collect();
+    let readable: Vec<PathBuf> = config_dirs
+        .iter()
+        .zip(&problems)
+        .filter(|(_, problem)| problem.is_none())
+        .map(|(dir, _)| dir.to_path_buf())
+        .collect();
+
+    let files = match SessionManager::load_configs(&readable) {
+        Ok(files) => files,
+        Err(err) => {
+            return vec![Check::new(
+                "config dir",
+                CheckStatus::Error,
+                format!("cannot load configs: {err}"),
+            )]
+        }
+    };
+
+    let mut checks = Vec::new();
+    for (config_dir, problem) in config_dirs.into_iter().zip(problems) {
+        match problem {
+            Some(problem) => checks.push(problem),
+            None => checks.extend(check_config_files(config_dir, &files)),
+        }
+    }
+    checks
+}
+
+/// Whether a config directory can be listed, or the check saying why not
+fn check_dir(config_dir: &Path) -> Option<Check> {
+    let subject = "config dir";
+    if !config_dir.exists() {
+        return Some(Check::new(
+            subject,
+            CheckStatus::Warning,
+            format!(
+                "{} does not exist; create a config with `tmuxrs new <name>`",
+                config_dir.display()
+            ),
+        ));
+    }
+    if !config_dir.is_dir() {
+        return Some(Check::new(
+            subject,
+            CheckStatus::Error,
+            format!("{} is not a directory", config_dir.display()),
+        ));
+    }
+    std::fs::read_dir(config_dir).err().map(|err| {
+        Check::new(
+            subject,
+            CheckStatus::Error,
+            format!("cannot read {}: {err}", config_dir.display()),
+        )
+    })
+}
+
+/// Report the configs of one directory: how many are in use, and why the others are not
+fn check_config_files(config_dir: &Path, files: &[ConfigFile]) -> Vec<Check> {
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    };
+
+    let mut valid = 0;
+    let mut findings = Vec::new();
+    for file in files
+        .iter()
+        .filter(|file| file.path.parent() == Some(config_dir))
+    {
+        let name = file_name(&file.path);
+        match (&file.config, &file.shadowed_by) {
+            (Err(err), _) => findings.push(Check::new(name, CheckStatus::Error, err.to_string())),
+            (Ok(_), Some(shadowed_by)) => findings.push(Check::new(
+                name,
+                CheckStatus::Warning,
+                format!("not used: shadowed by {}", shadowed_by.display()),
+            )),
+            (Ok(_), None) => valid += 1,
+        }
+    }
+    for path in SessionManager::ignored_files(config_dir).unwrap_or_default() {
+        findings.push(Check::new(
+            file_name(&path),
+            CheckStatus::Warning,
+            "ignored by `tmuxrs list`: not a .yml or .yaml file",
+        ));
+    }
+
+    let mut checks = vec![Check::new(
+        "config dir",
+        CheckStatus::Ok,
+        format!("{} ({valid} valid configs)", config_dir.display()),
+    )];
+    checks.extend(findings);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_executable_skips_non_executable_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("plain");
+        let bin = temp_dir.path().join("bin");
+        for dir in [&plain, &bin] {
+            std::fs::create_dir(dir).unwrap();
+            std::fs::write(dir.join("tmux"), "#!/bin/sh\necho tmux 9.9\n").unwrap();
+        }
+        std::fs::set_permissions(bin.join("tmux"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = std::env::join_paths([&plain, &bin]).unwrap();
+        assert_eq!(find_executable("tmux", &path), Some(bin.join("tmux")));
+
+        let check = check_tmux(Some(&path));
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(check.detail.starts_with("tmux 9.9 ("), "{}", check.detail);
+
+        let check = check_tmux(Some(plain.as_os_str()));
+        assert_eq!(check.status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_environment_variable_checks() {
+        let some = |value: &str| Some(value.to_string());
+        assert_eq!(check_shell(some("/bin/zsh")).status, CheckStatus::Ok);
+        assert_eq!(check_shell(some("")).status, CheckStatus::Warning);
+
+        assert_eq!(check_editor(some("vim"), None).detail, "vim");
+        assert_eq!(
+            check_editor(None, some("code --wait")).detail,
+            "not set, $VISUAL (code --wait) is used"
+        );
+        // Like `tmuxrs edit`, $VISUAL wins when both are set
+        assert_eq!(
+            check_editor(some("vim"), some("code --wait")).detail,
+            "vim, but $VISUAL (code --wait) is used"
+        );
+        assert_eq!(check_editor(None, None).status, CheckStatus::Warning);
+    }
+
+    #[test]
+    fn test_config_dir_reports_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("good.yml"),
+            "name: good\nwindows:\n  - shell: ''\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("broken.yaml"),
+            "name: broken\nwindows:\n  - editor: [vim\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "todo").unwrap();
+
+        let checks = check_config_dirs(&[temp_dir.path().to_path_buf()]);
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0].status, CheckStatus::Ok);
+        assert!(checks[0].detail.ends_with("(1 valid configs)"));
+
+        assert_eq!(checks[1].subject, "broken.yaml");
+        assert_eq!(checks[1].status, CheckStatus::Error);
+        assert!(
+            checks[1].detail.contains("broken.yaml:4:1"),
+            "{}",
+            checks[1].detail
+        );
+
+        assert_eq!(checks[2].subject, "notes.txt");
+        assert_eq!(checks[2].status, CheckStatus::Warning);
+        assert!(has_errors(&checks));
+
+        let missing = check_config_dirs(&[temp_dir.path().join("missing")]);
+        assert_eq!(missing[0].status, CheckStatus::Warning);
+        assert!(!has_errors(&missing));
+    }
+
+    #[test]
+    fn test_config_dirs_report_shadowed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("tmuxrs");
+        let second = temp_dir.path().join("tmuxinator");
+        for dir in [&first, &second] {
+            std::fs::create_dir(dir).unwrap();
+            std::fs::write(dir.join("app.yml"), "name: app\nwindows:\n  - shell: ''\n").unwrap();
+        }
+        std::fs::write(
+            second.join("web.yml"),
+            "name: web\nwindows:\n  - shell: ''\n",
+        )
+        .unwrap();
+
+        // Directories after the first are only checked when they exist
+        let checks = check_config_dirs(&[
+            first.clone(),
+            temp_dir.path().join("missing"),
+            second.clone(),
+        ]);
+        let summary: Vec<(&str, CheckStatus)> = checks
+            .iter()
+            .map(|check| (check.subject.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("config dir", CheckStatus::Ok),
+                ("config dir", CheckStatus::Ok),
+                ("app.yml", CheckStatus::Warning),
+            ]
+        );
+        assert!(checks[1].detail.ends_with("(1 valid configs)"));
+        assert_eq!(
+            checks[2].detail,
+            format!("not used: shadowed by {}", first.join("app.yml").display())
+        );
+        assert!(!has_errors(&checks));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::error::{Result, TmuxrsError};
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `vi`
+pub fn editor() -> String {
+    choose_editor(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok())
+}
+
+fn choose_editor(visual: Option<String>, editor: Option<String>) -> String {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Open a file in the user's editor and wait for it to exit
+pub fn edit_file(file_path: &Path) -> Result<()> {
+    edit_file_with(&editor(), file_path)
+}
+
+/// Open a file with the given editor command and wait for it to exit
+///
+/// The command goes through `sh`, so values like `code --wait` work.
+pub fn edit_file_with(editor: &str, file_path: &Path) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg("sh")
+        .arg(file_path)
+        .status()?;
+
+    if !status.success() {
+        return Err(TmuxrsError::EditorFailed {
+            editor: editor.to_string(),
+            status,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_choose_editor_prefers_visual() {
+        let some = |editor: &str| Some(editor.to_string());
+        assert_eq!(
+            choose_editor(some("code --wait"), some("vim")),
+            "code --wait"
+        );
+        assert_eq!(choose_editor(None, some("vim")), "vim");
+        assert_eq!(choose_editor(some(" "), some("nano")), "nano");
+        assert_eq!(choose_editor(None, None), "vi");
+    }
+
+    #[test]
+    fn test_edit_file_with_runs_editor_on_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("my project.yml");
+        std::fs::write(&file_path, "name: before\n").unwrap();
+
+        // The editor gets the path as a single argument, even with spaces in it
+        edit_file_with("sed -i s/before/after/", &file_path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "name: after\n"
+        );
+
+        match edit_file_with("false", &file_path) {
+            Err(TmuxrsError::EditorFailed { editor, .. }) => assert_eq!(editor, "false"),
+            other => panic!("Expected EditorFailed error, got {other:?}"),
+        }
+    }
+}
@@ -7,15 +7,76 @@ pub enum TmuxrsError {
     #[error("Configuration file not found: {0}")]
     ConfigNotFound(String),
 
+    #[error("Configuration file already exists: {0} (use --force to overwrite)")]
+    ConfigExists(String),
+
+    #[error("Session '{0}' is running (use --force to change its configuration anyway)")]
+    SessionRunning(String),
+
+    #[error(
+        "Several configurations have a root containing {directory}: {}; pass a name to choose one",
+        names.join(", ")
+    )]
+    AmbiguousConfig {
+        directory: String,
+        names: Vec<String>,
+    },
+
+    #[error("Root {root} of configuration '{name}' is not inside a git repository")]
+    NotInGitRepository { name: String, root: String },
+
+    #[error("Configuration '{name}' has no worktree '{worktree}' (see `tmuxrs worktrees {name}`)")]
+    WorktreeNotFound { name: String, worktree: String },
+
+    #[error(
+        "Several worktrees would get session '{session}': {}; rename one of their directories",
+        paths.join(", ")
+    )]
+    AmbiguousWorktree { session: String, paths: Vec<String> },
+
     #[error("Failed to parse YAML: {0}")]
     YamlError(#[from] serde_yaml::Error),
 
+    #[error("Invalid configuration file {0}")]
+    ConfigParse(#[from] crate::config::ConfigParseError),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Invalid layout for window '{window}': {source}")]
+    InvalidLayout {
+        window: String,
+        #[source]
+        source: crate::layout::LayoutError,
+    },
+
+    #[error("No saved sessions found at {0}")]
+    NoSavedState(String),
+
+    #[error("Unsupported state file version {found} (this tmuxrs reads version {supported})")]
+    UnsupportedStateVersion { found: String, supported: u32 },
+
     #[error("tmux command failed: {0}")]
     #[allow(dead_code)]
     TmuxError(String),
 
+    #[error("Failed to serialize JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Editor '{editor}' failed with {status}")]
+    EditorFailed {
+        editor: String,
+        status: std::process::ExitStatus,
+    },
+
+    #[error("Hook '{hook}' failed with {status}")]
+    HookFailed {
+        hook: String,
+        status: std::process::ExitStatus,
+    },
 }
 
 #[cfg(test)]
@@ -32,6 +93,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_config_display() {
+        let error = TmuxrsError::InvalidConfig("startup_window 'logs' not found".to_string());
+        let display = format!("{error}");
+        assert_eq!(
+            display,
+            "Invalid configuration: startup_window 'logs' not found"
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_config_display() {
+        let error = TmuxrsError::AmbiguousConfig {
+            directory: "/work/acme".to_string(),
+            names: vec!["acme".to_string(), "acme-ops".to_string()],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Several configurations have a root containing /work/acme: acme, acme-ops; pass a name to choose one"
+        );
+    }
+
     #[test]
     fn test_tmux_error_display() {
         let error = TmuxrsError::TmuxError("Session already exists".to_string());
@@ -62,6 +145,23 @@ mod tests {
         assert!(display.contains("File not found"));
     }
 
+    #[test]
+    fn test_hook_failed_display() {
+        let status = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .status()
+            .unwrap();
+        let error = TmuxrsError::HookFailed {
+            hook: "on_project_start".to_string(),
+            status,
+        };
+        let display = format!("{error}");
+        assert_eq!(
+            display,
+            "Hook 'on_project_start' failed with exit status: 3"
+        );
+    }
+
     #[test]
     fn test_error_debug_format() {
         let error = TmuxrsError::ConfigNotFound("test.yml".to_string());
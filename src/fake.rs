@@ -0,0 +1,708 @@
+use crate::backend::TmuxBackend;
+use crate::error::{Result, TmuxrsError};
+use crate::tmux::TmuxCommand;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name given to windows created without `-n`
+const DEFAULT_WINDOW_NAME: &str = "shell";
+
+/// Program a pane reports as running before any keys are sent to it
+const SHELL_COMMAND: &str = "sh";
+
+/// `#{session_created}` of the first session; later ones are created a second apart
+const FIRST_SESSION_CREATED: u64 = 1_700_000_000;
+
+/// `#{pane_pid}` of the first pane; later panes get the next IDs
+const FIRST_PANE_PID: u32 = 1000;
+
+/// A pane in a `FakeWindow`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakePane {
+    /// Process ID of the pane's shell, new for every pane and respawn
+    pub pid: u32,
+    /// Directory the pane's shell was started in
+    pub working_dir: PathBuf,
+    /// Keys sent with `send-keys`, one entry per call, without the trailing `Enter`
+    pub sent_keys: Vec<String>,
+}
+
+impl FakePane {
+    /// What `#{pane_current_command}` reports: the program of the last keys
+    /// sent, or the shell when nothing was sent
+    pub fn current_command(&self) -> &str {
+        self.sent_keys
+            .last()
+            .and_then(|keys| keys.split_whitespace().next())
+            .unwrap_or(SHELL_COMMAND)
+    }
+}
+
+/// A window in a `FakeSession`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeWindow {
+    pub index: usize,
+    pub name: String,
+    /// The layout last applied with `select-layout`
+    pub layout: Option<String>,
+    /// Panes in index order
+    pub panes: Vec<FakePane>,
+    /// Position of the active pane in `panes`
+    pub active_pane: usize,
+}
+
+/// A session held by `FakeBackend`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeSession {
+    pub name: String,
+    pub working_dir: PathBuf,
+    /// Windows in index order
+    pub windows: Vec<FakeWindow>,
+    /// Index of the active window
+    pub active_window: usize,
+    /// Options set with `set-option`
+    pub options: BTreeMap<String, String>,
+    /// Commands set with `set-hook`, by hook name
+    pub hooks: BTreeMap<String, String>,
+    pub attached: bool,
+    /// Creation time, in seconds since the Unix epoch
+    pub created: u64,
+}
+
+impl FakeSession {
+    /// Look up a window by name
+    pub fn window(&self, name: &str) -> Option<&FakeWindow> {
+        self.windows.iter().find(|window| window.name == name)
+    }
+
+    /// Window names in index order
+    pub fn window_names(&self) -> Vec<&str> {
+        self.windows
+            .iter()
+            .map(|window| window.name.as_str())
+            .collect()
+    }
+
+    /// A numeric option such as `base-index`, defaulting to 0 like tmux
+    fn index_option(&self, name: &str) -> usize {
+        self.options
+            .get(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Resolve the window part of a target: empty for the active window, `^`,
+    /// `$`, an index or a name
+    fn window_position(&self, spec: &str) -> Option<usize> {
+        match spec {
+            "" => self
+                .windows
+                .iter()
+                .position(|window| window.index == self.active_window),
+            "^" => (!self.windows.is_empty()).then_some(0),
+            "$" => self.windows.len().checked_sub(1),
+            _ => spec
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| self.windows.iter().position(|w| w.index == index))
+                .or_else(|| {
+                    // Like tmux, refuse a name that several windows share
+                    let mut matches = self
+                        .windows
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, w)| w.name == spec);
+                    let (position, _) = matches.next()?;
+                    matches.next().is_none().then_some(position)
+                }),
+        }
+    }
+
+    /// Resolve the window and pane part of a target to (window, pane) positions
+    ///
+    /// A target without a pane refers to the active pane of the window.
+    fn pane_position(&self, spec: &str) -> Result<(usize, usize)> {
+        if let Some(window) = self.window_position(spec) {
+            return Ok((window, self.windows[window].active_pane));
+        }
+
+        let not_found = || TmuxrsError::TmuxError(format!("can't find pane: {spec}"));
+        let (window_spec, pane_spec) = spec.rsplit_once('.').ok_or_else(not_found)?;
+        let window = self.window_position(window_spec).ok_or_else(not_found)?;
+        let pane = pane_spec
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| index.checked_sub(self.index_option("pane-base-index")))
+            .filter(|pane| *pane < self.windows[window].panes.len())
+            .ok_or_else(not_found)?;
+        Ok((window, pane))
+    }
+}
+
+/// A lifecycle hook command that `FakeBackend` recorded instead of running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeHook {
+    pub hook: String,
+    pub command: String,
+    pub working_dir: PathBuf,
+}
+
+/// In-memory tmux server for tests
+///
+/// Understands the commands that tmuxrs itself issues, including chains built
+/// with `TmuxCommand::chain`, and keeps every invocation it receives in
+/// `history`. Unknown commands and targets fail with the same kind of error
+/// tmux would report. Lifecycle hooks are recorded in `lifecycle_hooks`
+/// rather than run.
+#[derive(Debug, Default)]
+pub struct FakeBackend {
+    sessions: RefCell<Vec<FakeSession>>,
+    history: RefCell<Vec<TmuxCommand>>,
+    lifecycle_hooks: RefCell<Vec<FakeHook>>,
+    panes_created: Cell<u32>,
+}
+
+impl FakeBackend {
+    /// Create a backend with no sessions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A snapshot of a session by name
+    pub fn session(&self, name: &str) -> Option<FakeSession> {
+        self.sessions
+            .borrow()
+            .iter()
+            .find(|session| session.name == name)
+            .cloned()
+    }
+
+    /// A snapshot of all sessions in creation order
+    pub fn sessions(&self) -> Vec<FakeSession> {
+        self.sessions.borrow().clone()
+    }
+
+    /// Every command received so far, in order
+    pub fn history(&self) -> Vec<TmuxCommand> {
+        self.history.borrow().clone()
+    }
+
+    /// Every lifecycle hook command run so far, in order
+    pub fn lifecycle_hooks(&self) -> Vec<FakeHook> {
+        self.lifecycle_hooks.borrow().clone()
+    }
+
+    /// A pane with a fresh shell in `working_dir`
+    fn new_pane(&self, working_dir: PathBuf) -> FakePane {
+        let pid = FIRST_PANE_PID + self.panes_created.get();
+        self.panes_created.set(self.panes_created.get() + 1);
+        FakePane {
+            pid,
+            working_dir,
+            sent_keys: Vec::new(),
+        }
+    }
+
+    /// Apply the commands of an invocation to the model in order
+    ///
+    /// Like tmux, a chain stops at the first failing command.
+    fn run(&self, command: &TmuxCommand) -> Result<String> {
+        let commands = command.commands();
+        if commands.is_empty() {
+            return Err(TmuxrsError::TmuxError("no command given".to_string()));
+        }
+
+        let mut output = String::new();
+        for args in &commands {
+            output.push_str(&self.run_one(args)?);
+        }
+        Ok(output)
+    }
+
+    /// Apply a single command to the model
+    fn run_one(&self, args: &[String]) -> Result<String> {
+        let (name, rest) = args
+            .split_first()
+            .ok_or_else(|| TmuxrsError::TmuxError("no command given".to_string()))?;
+        let args = FakeArgs::parse(rest);
+        let mut sessions = self.sessions.borrow_mut();
+
+        match name.as_str() {
+            "new-session" => {
+                let session_name = args
+                    .value('s')
+                    .map(str::to_string)
+                    .unwrap_or_else(|| sessions.len().to_string());
+                if sessions.iter().any(|session| session.name == session_name) {
+                    return Err(TmuxrsError::TmuxError(format!(
+                        "duplicate session: {session_name}"
+                    )));
+                }
+
+                let working_dir = PathBuf::from(args.value('c').unwrap_or_default());
+                let created = sessions
+                    .iter()
+                    .map(|session| session.created + 1)
+                    .max()
+                    .unwrap_or(FIRST_SESSION_CREATED);
+                sessions.push(FakeSession {
+                    name: session_name,
+                    windows: vec![FakeWindow {
+                        index: 0,
+                        name: args.value('n').unwrap_or(DEFAULT_WINDOW_NAME).to_string(),
+                        layout: None,
+                        panes: vec![self.new_pane(working_dir.clone())],
+                        active_pane: 0,
+                    }],
+                    working_dir,
+                    active_window: 0,
+                    options: BTreeMap::new(),
+                    hooks: BTreeMap::new(),
+                    attached: false,
+                    created,
+                });
+                Ok(String::new())
+            }
+            "has-session" => {
+                find_session(&mut sessions, args.target())?;
+                Ok(String::new())
+            }
+            "kill-session" => {
+                let (session_name, _) = split_target(args.target());
+                let position = sessions
+                    .iter()
+                    .position(|session| session.name == session_name)
+                    .ok_or_else(|| session_not_found(session_name))?;
+                sessions.remove(position);
+                Ok(String::new())
+            }
+            "kill-server" => {
+                sessions.clear();
+                Ok(String::new())
+            }
+            "attach-session" => {
+                find_session(&mut sessions, args.target())?.attached = true;
+                Ok(String::new())
+            }
+            "set-option" | "set-hook" => {
+                let session = find_session(&mut sessions, args.target())?;
+                let (key, value) = match args.positional.as_slice() {
+                    [key, value] => (key.to_string(), value.to_string()),
+                    _ => {
+                        return Err(TmuxrsError::TmuxError(format!(
+                            "{name}: expected a name and a value"
+                        )))
+                    }
+                };
+                if name == "set-option" {
+                    session.options.insert(key, value);
+                } else {
+                    session.hooks.insert(key, value);
+                }
+                Ok(String::new())
+            }
+            "list-sessions" => {
+                let format = args.value('F').unwrap_or("#{session_name}");
+                Ok(sessions
+                    .iter()
+                    .map(|session| {
+                        expand_format(
+                            format,
+                            &[
+                                ("session_name", session.name.clone()),
+                                ("session_windows", session.windows.len().to_string()),
+                                ("session_attached", u8::from(session.attached).to_string()),
+                                ("session_path", session.working_dir.display().to_string()),
+                                ("session_created", session.created.to_string()),
+                            ],
+                        ) + "\n"
+                    })
+                    .collect())
+            }
+            "list-windows" => {
+                let session = find_session(&mut sessions, args.target())?;
+                let format = args.value('F').unwrap_or("#{window_index}: #{window_name}");
+                Ok(session
+                    .windows
+                    .iter()
+                    .map(|window| {
+                        expand_format(
+                            format,
+                            &[
+                                ("window_index", window.index.to_string()),
+                                ("window_name", window.name.clone()),
+                                ("window_panes", window.panes.len().to_string()),
+                                ("window_layout", window.layout.clone().unwrap_or_default()),
+                            ],
+                        ) + "\n"
+                    })
+                    .collect())
+            }
+            "list-panes" => {
+                let (session_name, spec) = split_target(args.target());
+                let session = find_session(&mut sessions, session_name)?;
+                let position = session
+                    .window_position(spec)
+                    .ok_or_else(|| TmuxrsError::TmuxError(format!("can't find window: {spec}")))?;
+                let pane_base_index = session.index_option("pane-base-index");
+                let window = &session.windows[position];
+                let format = args
+                    .value('F')
+                    .unwrap_or("#{pane_index}: #{pane_current_path}");
+                Ok(window
+                    .panes
+                    .iter()
+                    .enumerate()
+                    .map(|(pane_index, pane)| {
+                        expand_format(
+                            format,
+                            &[
+                                ("window_index", window.index.to_string()),
+                                ("window_name", window.name.clone()),
+                                ("pane_index", (pane_base_index + pane_index).to_string()),
+                                ("pane_current_path", pane.working_dir.display().to_string()),
+                                ("pane_pid", pane.pid.to_string()),
+                                ("pane_current_command", pane.current_command().to_string()),
+                            ],
+                        ) + "\n"
+                    })
+                    .collect())
+            }
+            "new-window" => {
+                let (_, spec) = split_target(args.target());
+                let session = find_session(&mut sessions, args.target())?;
+                let index = match spec.parse::<usize>() {
+                    Ok(index) if session.windows.iter().any(|window| window.index == index) => {
+                        return Err(TmuxrsError::TmuxError(format!("index {index} in use")));
+                    }
+                    Ok(index) => index,
+                    Err(_) => {
+                        let mut index = session.index_option("base-index");
+                        while session.windows.iter().any(|window| window.index == index) {
+                            index += 1;
+                        }
+                        index
+                    }
+                };
+
+                let working_dir = args
+                    .value('c')
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| session.working_dir.clone());
+                session.windows.push(FakeWindow {
+                    index,
+                    name: args.value('n').unwrap_or(DEFAULT_WINDOW_NAME).to_string(),
+                    layout: None,
+                    panes: vec![self.new_pane(working_dir)],
+                    active_pane: 0,
+                });
+                session.windows.sort_by_key(|window| window.index);
+                session.active_window = index;
+                Ok(String::new())
+            }
+            // tmuxrs only moves windows with `-r`, to renumber them from base-index
+            "move-window" => {
+                let session = find_session(&mut sessions, args.target())?;
+                let base_index = session.index_option("base-index");
+                let active = session
+                    .windows
+                    .iter()
+                    .position(|window| window.index == session.active_window);
+                for (position, window) in session.windows.iter_mut().enumerate() {
+                    window.index = base_index + position;
+                }
+                if let Some(active) = active {
+                    session.active_window = base_index + active;
+                }
+                Ok(String::new())
+            }
+            "rename-window" | "select-window" | "select-layout" => {
+                let (session_name, spec) = split_target(args.target());
+                let session = find_session(&mut sessions, session_name)?;
+                let position = session
+                    .window_position(spec)
+                    .ok_or_else(|| TmuxrsError::TmuxError(format!("can't find window: {spec}")))?;
+                let window_index = session.windows[position].index;
+                let window = &mut session.windows[position];
+
+                match name.as_str() {
+                    "rename-window" => window.name = args.first_positional(name)?.to_string(),
+                    "select-layout" => {
+                        window.layout = Some(args.first_positional(name)?.to_string())
+                    }
+                    _ => session.active_window = window_index,
+                }
+                Ok(String::new())
+            }
+            "split-window" | "select-pane" | "respawn-pane" | "send-keys" | "capture-pane" => {
+                let (session_name, spec) = split_target(args.target());
+                let session = find_session(&mut sessions, session_name)?;
+                let (window, pane) = session.pane_position(spec)?;
+                let working_dir = args
+                    .value('c')
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| session.working_dir.clone());
+                let window = &mut session.windows[window];
+
+                match name.as_str() {
+                    // The new pane goes right after the one it splits and becomes active
+                    "split-window" => {
+                        window.panes.insert(pane + 1, self.new_pane(working_dir));
+                        window.active_pane = pane + 1;
+                    }
+                    "select-pane" => window.active_pane = pane,
+                    // The fake has no terminal, so a pane's contents are the keys it was sent
+                    "capture-pane" => {
+                        return Ok(window.panes[pane]
+                            .sent_keys
+                            .iter()
+                            .map(|keys| format!("{keys}\n"))
+                            .collect())
+                    }
+                    "respawn-pane" => {
+                        // The old shell is killed along with whatever it was sent
+                        let working_dir = args
+                            .value('c')
+                            .map_or_else(|| window.panes[pane].working_dir.clone(), PathBuf::from);
+                        window.panes[pane] = self.new_pane(working_dir);
+                    }
+                    _ => {
+                        let mut keys = args.positional.clone();
+                        if keys.last() == Some(&"Enter") {
+                            keys.pop();
+                        }
+                        window.panes[pane].sent_keys.push(keys.join(" "));
+                    }
+                }
+                Ok(String::new())
+            }
+            _ => Err(TmuxrsError::TmuxError(format!("unknown command: {name}"))),
+        }
+    }
+}
+
+impl TmuxBackend for FakeBackend {
+    fn execute(&self, command: TmuxCommand) -> Result<String> {
+        let output = self.run(&command);
+        self.history.borrow_mut().push(command);
+        output
+    }
+
+    fn execute_interactive(&self, command: TmuxCommand) -> Result<()> {
+        self.execute(command).map(|_| ())
+    }
+
+    /// Record a hook command instead of running it
+    fn run_hook(&self, hook: &str, command: &str, working_dir: &Path) -> Result<()> {
+        self.lifecycle_hooks.borrow_mut().push(FakeHook {
+            hook: hook.to_string(),
+            command: command.to_string(),
+            working_dir: working_dir.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    /// The keys last sent to the pane, when they started `program`
+    fn command_line(&self, pane_pid: u32, program: &str) -> Option<String> {
+        let sessions = self.sessions.borrow();
+        let pane = sessions
+            .iter()
+            .flat_map(|session| &session.windows)
+            .flat_map(|window| &window.panes)
+            .find(|pane| pane.pid == pane_pid)?;
+        (pane.current_command() == program)
+            .then(|| pane.sent_keys.last().cloned())
+            .flatten()
+    }
+}
+
+/// Command arguments split into flags, option values and positional arguments
+struct FakeArgs<'a> {
+    values: Vec<(char, &'a str)>,
+    positional: Vec<&'a str>,
+}
+
+impl<'a> FakeArgs<'a> {
+    /// Flags that take a value in the commands tmuxrs issues
+    const VALUE_FLAGS: &'static str = "tscnFlS";
+
+    fn parse(args: &'a [String]) -> Self {
+        let mut values = Vec::new();
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut chars = arg.chars();
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some('-'), Some(flag), None) if positional.is_empty() => {
+                    if Self::VALUE_FLAGS.contains(flag) {
+                        if let Some(value) = args.next() {
+                            values.push((flag, value.as_str()));
+                        }
+                    }
+                }
+                _ => positional.push(arg.as_str()),
+            }
+        }
+
+        Self { values, positional }
+    }
+
+    fn value(&self, flag: char) -> Option<&'a str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == flag)
+            .map(|(_, value)| *value)
+    }
+
+    fn target(&self) -> &'a str {
+        self.value('t').unwrap_or_default()
+    }
+
+    fn first_positional(&self, command: &str) -> Result<&'a str> {
+        self.positional
+            .first()
+            .copied()
+            .ok_or_else(|| TmuxrsError::TmuxError(format!("{command}: missing argument")))
+    }
+}
+
+/// Split a target into its session and window/pane parts
+fn split_target(target: &str) -> (&str, &str) {
+    // A leading `=` asks tmux for an exact match, which is the only kind the fake does
+    let target = target.strip_prefix('=').unwrap_or(target);
+    target.split_once(':').unwrap_or((target, ""))
+}
+
+fn session_not_found(name: &str) -> TmuxrsError {
+    TmuxrsError::TmuxError(format!("can't find session: {name}"))
+}
+
+/// Find the session named by the session part of a target
+fn find_session<'s>(sessions: &'s mut [FakeSession], target: &str) -> Result<&'s mut FakeSession> {
+    let (session_name, _) = split_target(target);
+    sessions
+        .iter_mut()
+        .find(|session| session.name == session_name)
+        .ok_or_else(|| session_not_found(session_name))
+}
+
+/// Replace `#{name}` variables in a tmux format string
+fn expand_format(format: &str, variables: &[(&str, String)]) -> String {
+    variables
+        .iter()
+        .fold(format.to_string(), |output, (name, value)| {
+            output.replace(&format!("#{{{name}}}"), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execute(backend: &FakeBackend, command: TmuxCommand) {
+        backend.execute(command).unwrap();
+    }
+
+    #[test]
+    fn test_fake_models_windows_and_panes() {
+        let backend = FakeBackend::new();
+        execute(
+            &backend,
+            TmuxCommand::new_session_command("app", Path::new("/srv/app")),
+        );
+        execute(
+            &backend,
+            TmuxCommand::rename_window_command("app", "^", "editor"),
+        );
+        execute(
+            &backend,
+            TmuxCommand::split_window_horizontal_command(
+                "app",
+                "editor",
+                "",
+                Some(Path::new("/srv/app/tests")),
+            ),
+        );
+        execute(
+            &backend,
+            TmuxCommand::send_keys_to_pane_command("app", "editor", 1, "cargo test"),
+        );
+        execute(
+            &backend,
+            TmuxCommand::select_layout_command("app", "editor", "tiled"),
+        );
+        execute(
+            &backend,
+            TmuxCommand::new_window_command("app", "server", None, None),
+        );
+        execute(
+            &backend,
+            TmuxCommand::send_keys_command("app", "server", "cargo run"),
+        );
+        execute(
+            &backend,
+            TmuxCommand::select_window_command("app", "editor"),
+        );
+
+        let session = backend.session("app").unwrap();
+        assert_eq!(session.window_names(), vec!["editor", "server"]);
+        assert_eq!(session.active_window, 0);
+
+        let editor = session.window("editor").unwrap();
+        assert_eq!(editor.layout.as_deref(), Some("tiled"));
+        assert_eq!(editor.panes.len(), 2);
+        assert_eq!(editor.panes[1].working_dir, PathBuf::from("/srv/app/tests"));
+        assert_eq!(editor.panes[1].sent_keys, vec!["cargo test"]);
+
+        let server = session.window("server").unwrap();
+        assert_eq!(server.index, 1);
+        assert_eq!(server.panes[0].working_dir, PathBuf::from("/srv/app"));
+        assert_eq!(server.panes[0].sent_keys, vec!["cargo run"]);
+
+        assert_eq!(backend.history().len(), 8);
+    }
+
+    #[test]
+    fn test_fake_queries_and_errors() {
+        let backend = FakeBackend::new();
+        assert!(backend
+            .execute(TmuxCommand::has_session_command("app"))
+            .is_err());
+
+        execute(
+            &backend,
+            TmuxCommand::new_session_command("app", Path::new("/srv/app")),
+        );
+        execute(
+            &backend,
+            TmuxCommand::new_window_command("app", "logs", None, None),
+        );
+        assert!(backend
+            .execute(TmuxCommand::has_session_command("app"))
+            .is_ok());
+        assert_eq!(
+            backend
+                .execute(TmuxCommand::list_window_names_command("app"))
+                .unwrap(),
+            "shell\nlogs\n"
+        );
+
+        assert!(backend
+            .execute(TmuxCommand::new_session_command("app", Path::new("/")))
+            .is_err());
+        assert!(backend
+            .execute(TmuxCommand::send_keys_to_pane_command(
+                "app", "logs", 3, "ls"
+            ))
+            .is_err());
+        assert!(backend
+            .execute(TmuxCommand::new().arg("bogus-command"))
+            .is_err());
+
+        execute(&backend, TmuxCommand::kill_session_command("app"));
+        assert!(backend.sessions().is_empty());
+    }
+}
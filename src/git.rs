@@ -0,0 +1,277 @@
+use std::path::{Path, PathBuf};
+
+/// The git working tree that contains a directory
+///
+/// Found by looking for `.git` in the directory and its parents, without
+/// running git. A `.git` file, as used by linked worktrees and submodules, is
+/// followed to the git directory it points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRepository {
+    /// Top-level directory of the working tree
+    pub root: PathBuf,
+    /// The repository's git directory (`.git`, or the one a `.git` file points to)
+    pub git_dir: PathBuf,
+    /// The git directory shared by all worktrees, when `root` is a linked worktree
+    pub common_dir: Option<PathBuf>,
+}
+
+impl GitRepository {
+    /// The working tree containing `dir`, if any
+    pub fn discover(dir: &Path) -> Option<GitRepository> {
+        dir.ancestors().find_map(|candidate| {
+            let dot_git = candidate.join(".git");
+            if dot_git.is_dir() {
+                return Some(GitRepository {
+                    root: candidate.to_path_buf(),
+                    git_dir: dot_git,
+                    common_dir: None,
+                });
+            }
+
+            let git_dir = read_gitdir_file(&dot_git)?;
+            // Linked worktrees have a `commondir` file pointing back to the main
+            // repository; submodules do not
+            let common_dir = std::fs::read_to_string(git_dir.join("commondir"))
+                .ok()
+                .map(|common_dir| normalize(&git_dir.join(common_dir.trim())));
+            Some(GitRepository {
+                root: candidate.to_path_buf(),
+                git_dir,
+                common_dir,
+            })
+        })
+    }
+
+    /// The name of the project: the directory of the main working tree
+    ///
+    /// For a linked worktree this is the name of the repository it belongs to,
+    /// not of the worktree directory. A bare main repository named `proj.git`
+    /// is called `proj`.
+    pub fn name(&self) -> Option<String> {
+        let project_dir = match &self.common_dir {
+            Some(common_dir) if common_dir.file_name()? == ".git" => common_dir.parent()?,
+            Some(common_dir) => common_dir.as_path(),
+            None => self.root.as_path(),
+        };
+        let name = project_dir.file_name()?.to_str()?;
+        Some(name.strip_suffix(".git").unwrap_or(name).to_string())
+    }
+
+    /// Whether `root` is a linked worktree rather than the main working tree
+    pub fn is_linked_worktree(&self) -> bool {
+        self.common_dir.is_some()
+    }
+
+    /// The top-level directory of the main working tree
+    ///
+    /// `None` for the linked worktrees of a bare repository, which has none.
+    pub fn main_root(&self) -> Option<PathBuf> {
+        match &self.common_dir {
+            Some(common_dir) if common_dir.file_name()? == ".git" => {
+                common_dir.parent().map(Path::to_path_buf)
+            }
+            Some(_) => None,
+            None => Some(self.root.clone()),
+        }
+    }
+
+    /// Every working tree of the repository: the main one first, if there is
+    /// one, then the linked worktrees sorted by path
+    ///
+    /// Linked worktrees are read from `worktrees/*/gitdir` in the shared git
+    /// directory; ones whose directory no longer exists are left out.
+    pub fn worktrees(&self) -> Vec<Worktree> {
+        let mut linked: Vec<PathBuf> = self
+            .common_dir
+            .as_ref()
+            .unwrap_or(&self.git_dir)
+            .join("worktrees")
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let gitdir = std::fs::read_to_string(entry.ok()?.path().join("gitdir")).ok()?;
+                let worktree = Path::new(gitdir.trim()).parent()?.to_path_buf();
+                worktree.is_dir().then(|| normalize(&worktree))
+            })
+            .collect();
+        linked.sort();
+
+        let main = self.main_root().map(|path| Worktree {
+            path,
+            is_main: true,
+        });
+        main.into_iter()
+            .chain(linked.into_iter().map(|path| Worktree {
+                path,
+                is_main: false,
+            }))
+            .collect()
+    }
+}
+
+/// A working tree of a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    /// Top-level directory of the working tree
+    pub path: PathBuf,
+    /// Whether this is the main working tree rather than a linked worktree
+    pub is_main: bool,
+}
+
+/// The git directory named by a `.git` file (`gitdir: <path>`)
+fn read_gitdir_file(dot_git: &Path) -> Option<PathBuf> {
+    if !dot_git.is_file() {
+        return None;
+    }
+    let content = std::fs::read_to_string(dot_git).ok()?;
+    let git_dir = content.lines().next()?.strip_prefix("gitdir:")?.trim();
+    let base = dot_git.parent()?;
+    Some(normalize(&base.join(git_dir)))
+}
+
+/// Resolve `..` and symlinks where the path exists, so names come out right
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Fixtures for tests of code that reads git repositories
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::path::{Path, PathBuf};
+
+    /// Lay out `worktree` as a linked worktree of the repository whose shared git
+    /// directory is `common_dir`, like `git worktree add` but without git
+    ///
+    /// Only the files `GitRepository` reads are written: the worktree's
+    /// `.git` file, and `gitdir` and `commondir` in `common_dir/worktrees/<name>`,
+    /// where the name is the worktree's directory name. Returns that git directory.
+    pub(crate) fn add_worktree(common_dir: &Path, worktree: &Path) -> std::io::Result<PathBuf> {
+        let name = worktree.file_name().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("worktree path {} has no name", worktree.display()),
+            )
+        })?;
+        let git_dir = common_dir.join("worktrees").join(name);
+        std::fs::create_dir_all(&git_dir)?;
+        std::fs::create_dir_all(worktree)?;
+
+        std::fs::write(git_dir.join("commondir"), "../..\n")?;
+        std::fs::write(
+            git_dir.join("gitdir"),
+            format!("{}\n", worktree.join(".git").display()),
+        )?;
+        std::fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", git_dir.display()),
+        )?;
+        Ok(git_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::add_worktree;
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A main repository at `<tmp>/myproj` with a subdirectory, a linked
+    /// worktree at `<tmp>/myproj-feature` and a submodule at `myproj/vendor/lib`
+    fn repositories() -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let base = std::fs::canonicalize(temp_dir.path()).unwrap();
+        let main = base.join("myproj");
+        std::fs::create_dir_all(main.join("src").join("api")).unwrap();
+
+        let worktree = base.join("myproj-feature");
+        add_worktree(&main.join(".git"), &worktree).unwrap();
+        std::fs::create_dir_all(worktree.join("src")).unwrap();
+
+        std::fs::create_dir_all(main.join(".git").join("modules").join("lib")).unwrap();
+        let submodule = main.join("vendor").join("lib");
+        std::fs::create_dir_all(&submodule).unwrap();
+        std::fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/lib\n").unwrap();
+
+        (temp_dir, base)
+    }
+
+    #[test]
+    fn test_discover_from_subdirectory() {
+        let (_temp_dir, base) = repositories();
+        let repo = GitRepository::discover(&base.join("myproj").join("src").join("api")).unwrap();
+        assert_eq!(repo.root, base.join("myproj"));
+        assert_eq!(repo.name().as_deref(), Some("myproj"));
+        assert!(!repo.is_linked_worktree());
+
+        assert_eq!(GitRepository::discover(&base), None);
+    }
+
+    #[test]
+    fn test_linked_worktree_is_named_after_its_repository() {
+        let (_temp_dir, base) = repositories();
+        let repo = GitRepository::discover(&base.join("myproj-feature").join("src")).unwrap();
+        assert_eq!(repo.root, base.join("myproj-feature"));
+        assert_eq!(repo.common_dir, Some(base.join("myproj").join(".git")));
+        assert_eq!(repo.name().as_deref(), Some("myproj"));
+        assert!(repo.is_linked_worktree());
+    }
+
+    #[test]
+    fn test_worktrees_of_repository() {
+        let (_temp_dir, base) = repositories();
+        // A worktree that was deleted without `git worktree remove`
+        let stale = base
+            .join("myproj")
+            .join(".git")
+            .join("worktrees")
+            .join("gone");
+        std::fs::create_dir_all(&stale).unwrap();
+        std::fs::write(
+            stale.join("gitdir"),
+            format!("{}\n", base.join("gone/.git").display()),
+        )
+        .unwrap();
+
+        let expected = vec![
+            Worktree {
+                path: base.join("myproj"),
+                is_main: true,
+            },
+            Worktree {
+                path: base.join("myproj-feature"),
+                is_main: false,
+            },
+        ];
+        for dir in [base.join("myproj"), base.join("myproj-feature").join("src")] {
+            let repo = GitRepository::discover(&dir).unwrap();
+            assert_eq!(repo.main_root(), Some(base.join("myproj")));
+            assert_eq!(repo.worktrees(), expected);
+        }
+    }
+
+    #[test]
+    fn test_submodule_is_its_own_project() {
+        let (_temp_dir, base) = repositories();
+        let submodule = base.join("myproj").join("vendor").join("lib");
+        let repo = GitRepository::discover(&submodule).unwrap();
+        assert_eq!(repo.root, submodule);
+        assert_eq!(
+            repo.git_dir,
+            base.join("myproj").join(".git").join("modules").join("lib")
+        );
+        assert_eq!(repo.name().as_deref(), Some("lib"));
+        assert!(!repo.is_linked_worktree());
+    }
+
+    #[test]
+    fn test_worktree_of_bare_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = std::fs::canonicalize(temp_dir.path()).unwrap();
+        add_worktree(&base.join("proj.git"), &base.join("main")).unwrap();
+
+        let repo = GitRepository::discover(&base.join("main")).unwrap();
+        assert_eq!(repo.name().as_deref(), Some("proj"));
+        assert_eq!(repo.main_root(), None);
+    }
+}
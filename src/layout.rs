@@ -0,0 +1,390 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Preset layouts understood by `select-layout`
+const PRESETS: &[&str] = &[
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-horizontal-mirrored",
+    "main-vertical",
+    "main-vertical-mirrored",
+    "tiled",
+];
+
+/// Why a window layout was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LayoutError {
+    #[error("'{0}' is neither a preset layout nor a custom layout string")]
+    UnknownPreset(String),
+
+    #[error("'{name}' is ambiguous between presets {}", .matches.join(", "))]
+    AmbiguousPreset { name: String, matches: Vec<String> },
+
+    #[error("malformed layout string at offset {offset}: {reason}")]
+    Malformed { offset: usize, reason: String },
+
+    #[error("layout checksum is {found:04x} but the layout string sums to {expected:04x}")]
+    ChecksumMismatch { expected: u16, found: u16 },
+
+    #[error("layout has {layout_panes} panes but the window defines {configured_panes}")]
+    PaneCountMismatch {
+        layout_panes: usize,
+        configured_panes: usize,
+    },
+}
+
+/// A window layout as written in the config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// One of tmux's preset layouts, by its full name
+    Preset(String),
+    /// A custom layout string, as printed by `#{window_layout}`
+    Custom(CustomLayout),
+}
+
+/// A parsed custom layout: a checksum followed by a tree of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomLayout {
+    pub checksum: u16,
+    pub root: LayoutCell,
+}
+
+/// A rectangle of a custom layout, either a pane or a split into child cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutCell {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+    pub kind: CellKind,
+}
+
+/// What a layout cell contains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellKind {
+    /// A single pane, with its pane id when the layout records one
+    Pane(Option<u32>),
+    /// Children side by side (`{...}`)
+    LeftRight(Vec<LayoutCell>),
+    /// Children stacked top to bottom (`[...]`)
+    TopBottom(Vec<LayoutCell>),
+}
+
+impl LayoutCell {
+    /// Number of panes in this cell and all of its children
+    pub fn pane_count(&self) -> usize {
+        match &self.kind {
+            CellKind::Pane(_) => 1,
+            CellKind::LeftRight(children) | CellKind::TopBottom(children) => {
+                children.iter().map(LayoutCell::pane_count).sum()
+            }
+        }
+    }
+}
+
+impl Layout {
+    /// Parse a layout and check that it fits a window with `pane_count` panes
+    ///
+    /// Presets fit any number of panes; custom layouts must describe exactly as
+    /// many panes as the window creates.
+    pub fn validate(layout: &str, pane_count: usize) -> Result<Self, LayoutError> {
+        let parsed: Layout = layout.parse()?;
+        if let Layout::Custom(custom) = &parsed {
+            let layout_panes = custom.root.pane_count();
+            if layout_panes != pane_count {
+                return Err(LayoutError::PaneCountMismatch {
+                    layout_panes,
+                    configured_panes: pane_count,
+                });
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Look up a preset by name, accepting unique prefixes like tmux does
+    fn preset(name: &str) -> Result<Self, LayoutError> {
+        if let Some(exact) = PRESETS.iter().find(|preset| **preset == name) {
+            return Ok(Layout::Preset(exact.to_string()));
+        }
+
+        let matches: Vec<String> = PRESETS
+            .iter()
+            .filter(|preset| preset.starts_with(name))
+            .map(|preset| preset.to_string())
+            .collect();
+        match matches.as_slice() {
+            [] => Err(LayoutError::UnknownPreset(name.to_string())),
+            [preset] => Ok(Layout::Preset(preset.clone())),
+            _ => Err(LayoutError::AmbiguousPreset {
+                name: name.to_string(),
+                matches,
+            }),
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = LayoutError;
+
+    /// Custom layouts start with a hexadecimal checksum and a comma; anything else is a preset name
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        let layout = layout.trim();
+        let is_custom = layout
+            .split_once(',')
+            .is_some_and(|(checksum, _)| u16::from_str_radix(checksum, 16).is_ok());
+        if !is_custom {
+            return Self::preset(layout);
+        }
+
+        let (checksum, body) = layout.split_once(',').unwrap_or_default();
+        let found = u16::from_str_radix(checksum, 16).unwrap_or_default();
+        let expected = checksum_of(body);
+        if found != expected {
+            return Err(LayoutError::ChecksumMismatch { expected, found });
+        }
+
+        let mut parser = Parser {
+            input: body.as_bytes(),
+            offset: 0,
+            base: checksum.len() + 1,
+        };
+        let root = parser.cell()?;
+        if parser.offset != body.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+
+        Ok(Layout::Custom(CustomLayout {
+            checksum: found,
+            root,
+        }))
+    }
+}
+
+/// Renders the layout as `select-layout` expects it
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layout::Preset(name) => write!(f, "{name}"),
+            Layout::Custom(custom) => write!(f, "{:04x},{}", custom.checksum, custom.root),
+        }
+    }
+}
+
+impl fmt::Display for LayoutCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{},{},{}", self.width, self.height, self.x, self.y)?;
+        let (open, children, close) = match &self.kind {
+            CellKind::Pane(Some(id)) => return write!(f, ",{id}"),
+            CellKind::Pane(None) => return Ok(()),
+            CellKind::LeftRight(children) => ('{', children, '}'),
+            CellKind::TopBottom(children) => ('[', children, ']'),
+        };
+        write!(f, "{open}")?;
+        for (index, child) in children.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{child}")?;
+        }
+        write!(f, "{close}")
+    }
+}
+
+/// The 16-bit rotating checksum tmux prefixes custom layouts with
+pub fn checksum_of(body: &str) -> u16 {
+    body.bytes().fold(0u16, |checksum, byte| {
+        let rotated = (checksum >> 1) | ((checksum & 1) << 15);
+        rotated.wrapping_add(u16::from(byte))
+    })
+}
+
+/// Recursive descent parser for the cell tree of a custom layout
+struct Parser<'a> {
+    input: &'a [u8],
+    offset: usize,
+    /// Offset of the cell tree within the whole layout string, for error messages
+    base: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> LayoutError {
+        LayoutError::Malformed {
+            offset: self.base + self.offset,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.offset).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), LayoutError> {
+        if self.peek() == Some(byte) {
+            self.offset += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, LayoutError> {
+        let start = self.offset;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.offset += 1;
+        }
+        let number = std::str::from_utf8(&self.input[start..self.offset])
+            .ok()
+            .and_then(|digits| digits.parse().ok());
+        number.ok_or_else(|| {
+            self.offset = start;
+            self.error("expected a number")
+        })
+    }
+
+    /// `WxH,X,Y` followed by `,ID` for a pane or a `{...}` / `[...]` list of children
+    fn cell(&mut self) -> Result<LayoutCell, LayoutError> {
+        let width = self.number()?;
+        self.expect(b'x')?;
+        let height = self.number()?;
+        self.expect(b',')?;
+        let x = self.number()?;
+        self.expect(b',')?;
+        let y = self.number()?;
+
+        let kind = match self.peek() {
+            Some(b'{') => CellKind::LeftRight(self.children(b'}')?),
+            Some(b'[') => CellKind::TopBottom(self.children(b']')?),
+            Some(b',') => {
+                // A comma starts either this pane's id or the next sibling cell (`WxH`)
+                let saved = self.offset;
+                self.offset += 1;
+                match self.number() {
+                    Ok(id) if self.peek() != Some(b'x') => CellKind::Pane(Some(id)),
+                    _ => {
+                        self.offset = saved;
+                        CellKind::Pane(None)
+                    }
+                }
+            }
+            _ => CellKind::Pane(None),
+        };
+
+        Ok(LayoutCell {
+            width,
+            height,
+            x,
+            y,
+            kind,
+        })
+    }
+
+    fn children(&mut self, close: u8) -> Result<Vec<LayoutCell>, LayoutError> {
+        self.offset += 1; // Opening bracket
+        let mut children = vec![self.cell()?];
+        while self.peek() == Some(b',') {
+            self.offset += 1;
+            children.push(self.cell()?);
+        }
+        self.expect(close)?;
+
+        if children.len() < 2 {
+            return Err(self.error("a split must have at least two cells"));
+        }
+        Ok(children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from `tmux list-windows -F '#{window_layout}'`
+    const THREE_PANES: &str = "6d71,160x48,0,0{80x48,0,0,0,79x48,81,0[79x23,81,0,1,79x24,81,24,2]}";
+    const ONE_PANE: &str = "cc00,160x48,0,0,3";
+
+    #[test]
+    fn test_parse_custom_layout_tree() {
+        let layout: Layout = THREE_PANES.parse().unwrap();
+        let Layout::Custom(custom) = &layout else {
+            panic!("Expected a custom layout, got {layout:?}");
+        };
+
+        assert_eq!(custom.checksum, 0x6d71);
+        assert_eq!(custom.root.pane_count(), 3);
+        match &custom.root.kind {
+            CellKind::LeftRight(children) => {
+                assert_eq!(children[0].kind, CellKind::Pane(Some(0)));
+                assert!(
+                    matches!(&children[1].kind, CellKind::TopBottom(cells) if cells.len() == 2)
+                );
+            }
+            other => panic!("Expected a left-right split, got {other:?}"),
+        }
+
+        // Rendering gives back the original string
+        assert_eq!(layout.to_string(), THREE_PANES);
+        assert_eq!(ONE_PANE.parse::<Layout>().unwrap().to_string(), ONE_PANE);
+    }
+
+    #[test]
+    fn test_checksum_matches_tmux() {
+        assert_eq!(checksum_of("160x48,0,0,3"), 0xcc00);
+        assert_eq!(
+            "bad0,160x48,0,0,3".parse::<Layout>(),
+            Err(LayoutError::ChecksumMismatch {
+                expected: 0xcc00,
+                found: 0xbad0
+            })
+        );
+    }
+
+    #[test]
+    fn test_presets_and_prefixes() {
+        assert_eq!(
+            "tiled".parse::<Layout>(),
+            Ok(Layout::Preset("tiled".to_string()))
+        );
+        assert_eq!(
+            "even-v".parse::<Layout>(),
+            Ok(Layout::Preset("even-vertical".to_string()))
+        );
+        assert_eq!(
+            "main-vertical".parse::<Layout>(),
+            Ok(Layout::Preset("main-vertical".to_string()))
+        );
+        assert!(matches!(
+            "main".parse::<Layout>(),
+            Err(LayoutError::AmbiguousPreset { .. })
+        ));
+        assert_eq!(
+            "main-verticle".parse::<Layout>(),
+            Err(LayoutError::UnknownPreset("main-verticle".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_validate_pane_count() {
+        assert!(Layout::validate(THREE_PANES, 3).is_ok());
+        assert_eq!(
+            Layout::validate(THREE_PANES, 2),
+            Err(LayoutError::PaneCountMismatch {
+                layout_panes: 3,
+                configured_panes: 2
+            })
+        );
+        assert!(Layout::validate("tiled", 7).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_layout() {
+        let body = "160x48,0,0{80x48,0,0,0";
+        let layout = format!("{:04x},{body}", checksum_of(body));
+
+        assert!(matches!(
+            layout.parse::<Layout>(),
+            Err(LayoutError::Malformed { offset: 27, .. })
+        ));
+    }
+}
@@ -71,8 +71,18 @@
 //! - No more scattered `.tmuxinator.yml` files across projects
 //! - Works regardless of where your project is located
 
+pub mod backend;
 pub mod cli;
 pub mod config;
+pub mod doctor;
+pub mod editor;
 pub mod error;
+pub mod fake;
+pub mod git;
+pub mod layout;
+pub mod plan;
 pub mod session;
+pub mod settings;
+pub mod snapshot;
+pub mod state;
 pub mod tmux;
@@ -1,16 +1,222 @@
+mod backend;
 mod cli;
 mod config;
+mod doctor;
+mod editor;
 mod error;
+// The fake backend used by the unit tests of the other modules
+#[cfg(test)]
+#[allow(dead_code)]
+mod fake;
+mod git;
+mod layout;
+mod plan;
 mod session;
+mod settings;
+mod snapshot;
+mod state;
 mod tmux;
 
 use clap::Parser;
-use cli::{Args, Command};
+use cli::{Args, Command, ListFormat, PlanFormat};
+use config::{Config, ConfigFile};
 use error::Result;
+use plan::DryRun;
 use session::SessionManager;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Print the steps recorded by a dry-run session manager
+fn print_dry_run(session_manager: &SessionManager<DryRun>, format: PlanFormat) -> Result<()> {
+    let steps = session_manager.backend().steps();
+    match format {
+        PlanFormat::Text => print!("{}", plan::render_text(&steps)),
+        PlanFormat::Json => println!("{}", plan::render_json(&steps)?),
+    }
+    Ok(())
+}
+
+/// Print `list` output for people, grouped by source directory, with invalid
+/// configs in a separate section and running sessions marked
+fn print_config_list(session_manager: &SessionManager, files: Vec<ConfigFile>) -> Result<()> {
+    let running: Vec<_> = session_manager
+        .summarize_configs(&files)?
+        .into_iter()
+        .filter(|summary| summary.running)
+        .map(|summary| summary.config_file)
+        .collect();
+
+    let mut configs = Vec::new();
+    let mut broken = Vec::new();
+    for file in files {
+        match file.config {
+            Ok(config) => configs.push((file.path, config, file.shadowed_by)),
+            Err(err) => broken.push(err),
+        }
+    }
+
+    if configs.is_empty() && broken.is_empty() {
+        println!("No configurations found");
+    }
+    if !configs.is_empty() {
+        println!("Available configurations:");
+        let mut current_dir = None;
+        for (path, config, shadowed_by) in &configs {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            if current_dir != Some(dir) {
+                println!("  {}/", Config::root_value(dir));
+                current_dir = Some(dir);
+            }
+
+            let root = config.root.as_deref().unwrap_or("~");
+            let status = match shadowed_by {
+                Some(other) => format!(" [shadowed by {}]", Config::root_value(other)),
+                None if running.contains(path) => " [running]".to_string(),
+                None => String::new(),
+            };
+            println!(
+                "    {} - {} ({} windows){status}",
+                config.name,
+                root,
+                config.windows.len()
+            );
+        }
+    }
+    if !broken.is_empty() {
+        if !configs.is_empty() {
+            println!();
+        }
+        println!("Invalid configurations:");
+        for err in &broken {
+            println!("  {}", err.to_string().replace('\n', "\n  "));
+        }
+    }
+    Ok(())
+}
+
+/// Print `list` output for scripts; invalid configs go to stderr so stdout stays parseable
+///
+/// `names` leaves out shadowed configs, so that each name is printed once.
+fn print_config_summaries(
+    session_manager: &SessionManager,
+    files: &[ConfigFile],
+    format: ListFormat,
+) -> Result<()> {
+    for err in files.iter().filter_map(|file| file.config.as_ref().err()) {
+        eprintln!("Invalid configuration {err}");
+    }
+
+    let summaries = session_manager.summarize_configs(files)?;
+    match format {
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&summaries)?),
+        ListFormat::Yaml => print!("{}", serde_yaml::to_string(&summaries)?),
+        ListFormat::Names | ListFormat::Text => {
+            for summary in summaries
+                .iter()
+                .filter(|summary| summary.shadowed_by.is_none())
+            {
+                println!("{}", summary.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the running sessions as a table, with whether each has a config
+fn print_sessions(session_manager: &SessionManager, config_dir: Option<&Path>) -> Result<()> {
+    let sessions = session_manager.list_sessions()?;
+    if sessions.is_empty() {
+        println!("No sessions running");
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let width = sessions
+        .iter()
+        .map(|session| session.name.len())
+        .chain(["SESSION".len()])
+        .max()
+        .unwrap_or_default();
+
+    println!("{:width$}  CONFIG  ATTACHED  WINDOWS  CREATED", "SESSION");
+    for session in &sessions {
+        // Worktree sessions use the config of their project
+        let config_name = SessionManager::split_worktree_session_name(&session.name)
+            .map_or(session.name.as_str(), |(config_name, _)| config_name);
+        let config = match session_manager.config_file(config_name, config_dir) {
+            Ok(_) => "yes",
+            Err(_) => "no",
+        };
+        println!(
+            "{:width$}  {config:6}  {:<8}  {:<7}  {} ago",
+            session.name,
+            session.attached,
+            session.windows,
+            session.age(now)
+        );
+    }
+    Ok(())
+}
+
+/// Print the worktrees of a config's repository as a table, with the session
+/// each one gets and whether it is running
+fn print_worktrees(
+    session_manager: &SessionManager,
+    name: &str,
+    config_dir: Option<&Path>,
+) -> Result<()> {
+    let worktrees = session_manager.worktree_sessions(name, config_dir)?;
+    let width = worktrees
+        .iter()
+        .map(|worktree| worktree.session.len())
+        .chain(["SESSION".len()])
+        .max()
+        .unwrap_or_default();
+
+    println!("{:width$}  RUNNING  PATH", "SESSION");
+    for worktree in &worktrees {
+        let running = if worktree.running { "yes" } else { "no" };
+        println!(
+            "{:width$}  {running:7}  {}",
+            worktree.session,
+            Config::root_value(&worktree.path)
+        );
+    }
+    Ok(())
+}
+
+/// Parse a config again after it was edited, and report any error with the offending line
+fn check_edited_config(config_file: &Path) {
+    match Config::parse_file(config_file) {
+        Ok(config) => println!(
+            "Configuration '{}' ({} windows) saved to {}",
+            config.name,
+            config.windows.len(),
+            config_file.display()
+        ),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Ask a yes/no question on the terminal; anything but "y" or "yes" means no
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    let config_dir = args.config_dir.as_deref();
     let session_manager = SessionManager::new();
 
     match args.command {
@@ -19,39 +225,138 @@ fn main() -> Result<()> {
             attach,
             no_attach,
             append,
+            dry_run,
+            format,
         } => {
             // Determine final attach behavior: --no-attach overrides --attach
             let should_attach = if no_attach { false } else { attach };
 
-            let result = session_manager.start_session_with_options(
-                name.as_deref(),
-                None,
-                should_attach,
-                append,
-            )?;
-            println!("{result}");
+            if dry_run {
+                // Appending depends on the windows the session already has, so
+                // ask the server; otherwise plan a new session
+                let session_manager = if append {
+                    session_manager.dry_run_on_server()
+                } else {
+                    session_manager.dry_run(false)
+                };
+                session_manager.start_session_with_options(
+                    name.as_deref(),
+                    config_dir,
+                    should_attach,
+                    append,
+                )?;
+                print_dry_run(&session_manager, format)?;
+            } else {
+                let result = session_manager.start_session_with_options(
+                    name.as_deref(),
+                    config_dir,
+                    should_attach,
+                    append,
+                )?;
+                println!("{result}");
+            }
+        }
+        Command::List { strict, format } => {
+            let files = session_manager.list_configs(config_dir)?;
+            let has_invalid = files.iter().any(|file| file.config.is_err());
+            match format {
+                ListFormat::Text => print_config_list(&session_manager, files)?,
+                format => print_config_summaries(&session_manager, &files, format)?,
+            }
+            if strict && has_invalid {
+                std::process::exit(1);
+            }
         }
-        Command::List => {
-            let configs = session_manager.list_configs(None)?;
-            if configs.is_empty() {
-                println!("No configurations found");
+        Command::Stop {
+            name,
+            dry_run,
+            format,
+        } => {
+            if dry_run {
+                // A dry run assumes the session is running, so stop has something to kill
+                let session_manager = session_manager.dry_run(true);
+                session_manager.stop_session_with_options(&name, config_dir)?;
+                print_dry_run(&session_manager, format)?;
             } else {
-                println!("Available configurations:");
-                for config in configs {
-                    let root = config.root.as_deref().unwrap_or("~");
-                    println!(
-                        "  {} - {} ({} windows)",
-                        config.name,
-                        root,
-                        config.windows.len()
-                    );
-                }
+                let result = session_manager.stop_session_with_options(&name, config_dir)?;
+                println!("{result}");
             }
         }
-        Command::Stop { name } => {
-            let result = session_manager.stop_session(&name)?;
+        Command::Debug { name } => {
+            let plan = session_manager.plan_session(name.as_deref(), config_dir, true)?;
+            for command in plan {
+                println!("{command:#}");
+            }
+        }
+        Command::Freeze {
+            name,
+            force,
+            no_commands,
+        } => {
+            let result = session_manager.freeze_session(&name, config_dir, force, !no_commands)?;
+            println!("{result}");
+        }
+        Command::Save { scrollback } => {
+            let result = session_manager.save_sessions(config_dir, None, scrollback)?;
             println!("{result}");
         }
+        Command::Restore { no_commands } => {
+            let result = session_manager.restore_sessions(None, !no_commands)?;
+            println!("{result}");
+        }
+        Command::New { name } => {
+            let root = std::env::current_dir()?;
+            let config_file = session_manager.create_config(&name, config_dir, &root)?;
+            editor::edit_file(&config_file)?;
+            check_edited_config(&config_file);
+        }
+        Command::Edit { name } => {
+            let config_file = session_manager.config_file(&name, config_dir)?;
+            editor::edit_file(&config_file)?;
+            check_edited_config(&config_file);
+        }
+        Command::Copy { src, dst, force } => {
+            println!(
+                "{}",
+                session_manager.copy_config(&src, &dst, config_dir, force)?
+            );
+        }
+        Command::Delete { name, yes, force } => {
+            let config_file = session_manager.config_file(&name, config_dir)?;
+            let question = format!("Delete configuration '{name}' ({})?", config_file.display());
+            if yes || confirm(&question)? {
+                println!(
+                    "{}",
+                    session_manager.delete_config(&name, config_dir, force)?
+                );
+            } else {
+                println!("Aborted");
+            }
+        }
+        Command::Rename { old, new, force } => {
+            println!(
+                "{}",
+                session_manager.rename_config(&old, &new, config_dir, force)?
+            );
+        }
+        Command::Ps => print_sessions(&session_manager, config_dir)?,
+        Command::Worktrees { name } => print_worktrees(&session_manager, &name, config_dir)?,
+        Command::Doctor => {
+            // A broken settings file is reported by the checks themselves
+            let config_dirs = match config_dir {
+                Some(dir) => vec![dir.to_path_buf()],
+                None => {
+                    Config::config_dirs().or_else(|_| Config::config_dir().map(|dir| vec![dir]))?
+                }
+            };
+            let checks = doctor::run_checks(&config_dirs);
+            for check in &checks {
+                println!("{check}");
+            }
+            if doctor::has_errors(&checks) {
+                std::process::exit(1);
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,247 @@
+use crate::backend::TmuxBackend;
+use crate::error::{Result, TmuxrsError};
+use crate::tmux::TmuxCommand;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// A single step recorded during a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlanStep {
+    /// A tmux invocation that would have been executed
+    Tmux {
+        command: TmuxCommand,
+        interactive: bool,
+    },
+    /// A lifecycle hook command that would have been run
+    Hook {
+        hook: String,
+        command: String,
+        working_dir: PathBuf,
+    },
+}
+
+/// tmux commands that only read the server's state, and are safe to run for real
+const QUERY_COMMANDS: &[&str] = &[
+    "has-session",
+    "list-sessions",
+    "list-windows",
+    "list-panes",
+    "display-message",
+    "show-options",
+];
+
+/// Records tmux invocations and hooks instead of running them
+///
+/// By default queries are answered as if the server were in a known state, so
+/// the recorded plan is the same with or without a tmux server: `has-session`
+/// succeeds only when `session_running` is true, and everything else returns no
+/// output. A recorder made `with_server` runs queries against a real server
+/// instead, so that plans that depend on its state, like appending windows,
+/// match what a real run would do.
+#[derive(Default)]
+pub struct DryRun {
+    session_running: bool,
+    server: Option<Box<dyn TmuxBackend>>,
+    steps: RefCell<Vec<PlanStep>>,
+}
+
+impl DryRun {
+    /// Create a recorder that reports the target session as running or not
+    pub fn new(session_running: bool) -> Self {
+        Self {
+            session_running,
+            server: None,
+            steps: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Create a recorder that answers queries from `server`
+    ///
+    /// Queries are still recorded. A server that cannot be reached answers
+    /// like one without sessions.
+    pub fn with_server(server: impl TmuxBackend + 'static) -> Self {
+        Self {
+            session_running: false,
+            server: Some(Box::new(server)),
+            steps: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The steps recorded so far, in order
+    pub fn steps(&self) -> Vec<PlanStep> {
+        self.steps.borrow().clone()
+    }
+}
+
+impl TmuxBackend for DryRun {
+    /// Record a non-interactive tmux command and return its simulated output,
+    /// or for a query, the server's answer
+    fn execute(&self, command: TmuxCommand) -> Result<String> {
+        let is_has_session = command
+            .args()
+            .first()
+            .is_some_and(|arg| arg == "has-session");
+        let is_query = command.commands().iter().all(|args| {
+            args.first()
+                .is_some_and(|name| QUERY_COMMANDS.contains(&name.as_str()))
+        });
+        self.steps.borrow_mut().push(PlanStep::Tmux {
+            command: command.clone(),
+            interactive: false,
+        });
+
+        if let (Some(server), true) = (&self.server, is_query) {
+            return server.execute(command).map_err(|err| match err {
+                TmuxrsError::TmuxError(_) => err,
+                err => TmuxrsError::TmuxError(format!("no tmux server (dry run): {err}")),
+            });
+        }
+        if is_has_session && !self.session_running {
+            return Err(TmuxrsError::TmuxError(
+                "can't find session (dry run)".to_string(),
+            ));
+        }
+        Ok(String::new())
+    }
+
+    /// Record an interactive tmux command such as `attach-session`
+    fn execute_interactive(&self, command: TmuxCommand) -> Result<()> {
+        self.steps.borrow_mut().push(PlanStep::Tmux {
+            command,
+            interactive: true,
+        });
+        Ok(())
+    }
+
+    /// Record a hook command that would run in `working_dir`
+    fn run_hook(&self, hook: &str, command: &str, working_dir: &Path) -> Result<()> {
+        self.steps.borrow_mut().push(PlanStep::Hook {
+            hook: hook.to_string(),
+            command: command.to_string(),
+            working_dir: working_dir.to_path_buf(),
+        });
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for DryRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DryRun")
+            .field("session_running", &self.session_running)
+            .field("server", &self.server.is_some())
+            .field("steps", &self.steps)
+            .finish()
+    }
+}
+
+/// Render steps as a shell script, one step per line
+///
+/// Chained tmux commands are continued over several lines. Hooks are wrapped
+/// in a subshell that changes to their working directory and are annotated
+/// with the hook name.
+pub fn render_text(steps: &[PlanStep]) -> String {
+    let mut output = String::new();
+    for step in steps {
+        match step {
+            PlanStep::Tmux { command, .. } => output.push_str(&format!("{command:#}")),
+            PlanStep::Hook {
+                hook,
+                command,
+                working_dir,
+            } => output.push_str(&format!(
+                "(cd {} && {command})  # {hook}",
+                TmuxCommand::quote(&working_dir.to_string_lossy())
+            )),
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Render steps as a pretty-printed JSON array
+pub fn render_json(steps: &[PlanStep]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(steps)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_commands_in_order() {
+        let dry_run = DryRun::new(false);
+
+        dry_run
+            .execute(TmuxCommand::kill_session_command("first"))
+            .unwrap();
+        dry_run
+            .execute_interactive(TmuxCommand::attach_session_command("second"))
+            .unwrap();
+
+        assert_eq!(
+            dry_run.steps(),
+            vec![
+                PlanStep::Tmux {
+                    command: TmuxCommand::kill_session_command("first"),
+                    interactive: false,
+                },
+                PlanStep::Tmux {
+                    command: TmuxCommand::attach_session_command("second"),
+                    interactive: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_has_session_follows_assumed_state() {
+        let has_session = || TmuxCommand::new().arg("has-session").arg("-t").arg("app");
+
+        assert!(DryRun::new(false).execute(has_session()).is_err());
+        assert!(DryRun::new(true).execute(has_session()).is_ok());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let dry_run = DryRun::new(false);
+        dry_run
+            .run_hook(
+                "on_project_start",
+                "docker compose up -d",
+                Path::new("/srv/app"),
+            )
+            .unwrap();
+        dry_run
+            .execute(TmuxCommand::new_session_command(
+                "app",
+                Path::new("/srv/app"),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            render_text(&dry_run.steps()),
+            "(cd /srv/app && docker compose up -d)  # on_project_start\n\
+             tmux new-session -d -s app -c /srv/app\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let dry_run = DryRun::new(false);
+        dry_run
+            .execute(TmuxCommand::kill_session_command("app").socket("/tmp/s"))
+            .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_json(&dry_run.steps()).unwrap()).unwrap();
+        assert_eq!(json[0]["type"], "tmux");
+        assert_eq!(json[0]["interactive"], false);
+        assert_eq!(
+            json[0]["command"]["args"],
+            serde_json::json!(["kill-session", "-t", "app"])
+        );
+        assert_eq!(json[0]["command"]["socket_path"], "/tmp/s");
+    }
+}
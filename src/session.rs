@@ -1,18 +1,79 @@
-use crate::config::Config;
+use crate::backend::{ProcessBackend, TmuxBackend};
+use crate::config::{
+    expand_path, Config, ConfigFile, Hook, PaneConfig, SplitDirection, WindowConfig, WindowLayout,
+    WindowTarget,
+};
 use crate::error::{Result, TmuxrsError};
-use crate::tmux::TmuxCommand;
+use crate::git::{GitRepository, Worktree};
+use crate::layout::Layout;
+use crate::plan::DryRun;
+use crate::snapshot::{SessionSnapshot, PANE_FORMAT, SESSION_FORMAT, WINDOW_FORMAT};
+use crate::state::SavedState;
+use crate::tmux::{SessionInfo, TmuxCommand, SESSION_INFO_FORMAT};
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 
+/// What a single window runs: one command, or a set of panes with a layout
+#[derive(Clone, Copy)]
+enum WindowContent<'a> {
+    Command(&'a str),
+    Layout(&'a WindowLayout),
+}
+
+/// How the panes of a window are created by splitting, from `pane_positions`
+struct PaneSplits {
+    /// For every pane after the first, the position of the pane it splits and
+    /// the position it is created at
+    splits: Vec<(usize, usize)>,
+    /// Where every pane ends up, in config order
+    positions: Vec<usize>,
+}
+
+/// A valid config as shown by `tmuxrs list --format json|yaml`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigSummary {
+    pub name: String,
+    /// The session root with `~` and environment variables expanded
+    pub root: PathBuf,
+    pub windows: usize,
+    pub config_file: PathBuf,
+    /// The directory of the search path the config was found in
+    pub source_dir: PathBuf,
+    /// The config used instead of this one, which has the same name
+    pub shadowed_by: Option<PathBuf>,
+    /// Whether the session is running; like `start`, it is named after the
+    /// config file rather than the `name` field. Always false for a shadowed
+    /// config, since `start` does not use it.
+    pub running: bool,
+}
+
+/// A working tree of a config's repository, as shown by `tmuxrs worktrees`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorktreeSession {
+    /// The config name for the main working tree, `<config>@<worktree>` otherwise
+    pub session: String,
+    pub path: PathBuf,
+    /// Whether the session is running
+    pub running: bool,
+}
+
+/// Separates the config name from the worktree name in a worktree session name
+pub const WORKTREE_SEPARATOR: char = '@';
+
 /// Session manager for tmuxrs
+///
+/// Generic over the `TmuxBackend` that runs tmux commands, so that library
+/// users can test against `fake::FakeBackend` instead of a tmux server.
 #[derive(Default)]
-pub struct SessionManager {
+pub struct SessionManager<B = ProcessBackend> {
+    backend: B,
     socket_path: Option<PathBuf>,
 }
 
 impl SessionManager {
     /// Create a new session manager
     pub fn new() -> Self {
-        Self { socket_path: None }
+        Self::default()
     }
 
     /// Create a new session manager with a custom socket path
@@ -20,19 +81,48 @@ impl SessionManager {
     pub fn with_socket<P: AsRef<Path>>(socket_path: P) -> Self {
         Self {
             socket_path: Some(socket_path.as_ref().to_path_buf()),
+            ..Self::default()
         }
     }
+}
 
-    /// Expand tilde (~) and environment variables in paths using shellexpand
-    fn expand_path(path: &str) -> Result<PathBuf> {
-        // Try full expansion first (handles both tilde and environment variables)
-        match shellexpand::full(path) {
-            Ok(expanded) => Ok(PathBuf::from(expanded.as_ref())),
-            Err(_) => {
-                // Fallback: try basic tilde expansion only
-                let expanded = shellexpand::tilde(path);
-                Ok(PathBuf::from(expanded.as_ref()))
-            }
+impl<B: TmuxBackend> SessionManager<B> {
+    /// Create a session manager that runs tmux commands through the given backend
+    #[allow(dead_code)]
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            backend,
+            socket_path: None,
+        }
+    }
+
+    /// The backend tmux commands are run through
+    #[allow(dead_code)]
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// A session manager on the same socket that records tmux commands and
+    /// hooks instead of running them
+    ///
+    /// `session_running` decides whether the target session is treated as
+    /// already running. The recorded steps are available from `backend().steps()`.
+    pub fn dry_run(self, session_running: bool) -> SessionManager<DryRun> {
+        SessionManager {
+            backend: DryRun::new(session_running),
+            socket_path: self.socket_path,
+        }
+    }
+
+    /// Like `dry_run`, but queries, such as whether the session is running and
+    /// which windows it has, are answered by this manager's tmux server
+    pub fn dry_run_on_server(self) -> SessionManager<DryRun>
+    where
+        B: 'static,
+    {
+        SessionManager {
+            backend: DryRun::with_server(self.backend),
+            socket_path: self.socket_path,
         }
     }
 
@@ -42,356 +132,1806 @@ impl SessionManager {
         self.start_session_with_options(name, config_dir, true, false)
     }
 
-    /// Start a session with full options control
-    pub fn start_session_with_options(
-        &self,
-        name: Option<&str>,
+    /// Start a session with full options control
+    pub fn start_session_with_options(
+        &self,
+        name: Option<&str>,
+        config_dir: Option<&Path>,
+        attach: bool,
+        append: bool,
+    ) -> Result<String> {
+        let session_name = self.resolve_session_name(name, config_dir)?;
+
+        // Check if session already exists
+        if self.session_exists(&session_name)? {
+            // The config is optional here so that sessions started by hand can still be attached to
+            let config = if append {
+                Some(self.load_config(&session_name, config_dir)?)
+            } else {
+                self.load_optional_config(&session_name, config_dir)?
+            };
+
+            if let Some(config) = &config {
+                let root_path = SessionManager::root_path(config);
+                self.run_hook(
+                    "on_project_start",
+                    config.on_project_start.as_ref(),
+                    &root_path,
+                )?;
+                self.run_hook(
+                    "on_project_restart",
+                    config.on_project_restart.as_ref(),
+                    &root_path,
+                )?;
+
+                if append {
+                    return self.append_to_session(&session_name, config, attach);
+                }
+            }
+
+            if attach {
+                // Attach to existing session
+                match self.execute_interactive(TmuxCommand::attach_session_command(&session_name)) {
+                    Ok(()) => {
+                        // This line should never be reached in practice because
+                        // successful attach takes over the terminal process
+                        return Ok(format!("Attached to existing session '{session_name}'"));
+                    }
+                    Err(err) => {
+                        // Attach failed - could be no TTY, session doesn't exist, etc.
+                        return Err(TmuxrsError::TmuxError(format!(
+                            "Failed to attach to session '{session_name}': {err}"
+                        )));
+                    }
+                }
+            } else {
+                return Ok(format!("Session '{session_name}' already exists"));
+            }
+        }
+
+        // Load configuration
+        let config = self.load_config(&session_name, config_dir)?;
+        let root_path = SessionManager::root_path(&config);
+        let startup_target = SessionManager::startup_target(&config)?;
+        SessionManager::validate_layouts(&config)?;
+
+        self.run_hook(
+            "on_project_start",
+            config.on_project_start.as_ref(),
+            &root_path,
+        )?;
+        self.run_hook(
+            "on_project_first_start",
+            config.on_project_first_start.as_ref(),
+            &root_path,
+        )?;
+
+        // Create session, windows and panes
+        let plan = self.session_plan(&session_name, &config, &root_path, startup_target)?;
+        self.execute_plan(plan)?;
+
+        // Handle attachment
+        if attach {
+            match self.execute_interactive(TmuxCommand::attach_session_command(&session_name)) {
+                Ok(()) => {
+                    // This line should never be reached in practice because
+                    // successful attach takes over the terminal process
+                    Ok(format!("Started and attached to session '{session_name}'"))
+                }
+                Err(err) => {
+                    // Attach failed - provide helpful error message
+                    Err(TmuxrsError::TmuxError(format!(
+                        "Started session '{session_name}' but failed to attach: {err}"
+                    )))
+                }
+            }
+        } else {
+            Ok(format!("Started detached session '{session_name}'"))
+        }
+    }
+
+    /// Build the tmux invocations that `start` would run for a new session, without running them
+    ///
+    /// Like `start`, the session is built by a single chained invocation, which
+    /// is followed by `attach-session` when `attach` is set. The session is
+    /// assumed not to exist yet. Lifecycle hooks run outside tmux and are not
+    /// part of the plan.
+    pub fn plan_session(
+        &self,
+        name: Option<&str>,
+        config_dir: Option<&Path>,
+        attach: bool,
+    ) -> Result<Vec<TmuxCommand>> {
+        let session_name = self.resolve_session_name(name, config_dir)?;
+
+        let config = self.load_config(&session_name, config_dir)?;
+        let root_path = SessionManager::root_path(&config);
+        let startup_target = SessionManager::startup_target(&config)?;
+        SessionManager::validate_layouts(&config)?;
+
+        let mut plan = vec![TmuxCommand::chain(self.session_plan(
+            &session_name,
+            &config,
+            &root_path,
+            startup_target,
+        )?)];
+        if attach {
+            plan.push(self.on_socket(TmuxCommand::attach_session_command(&session_name)));
+        }
+
+        Ok(plan)
+    }
+
+    /// Build the ordered commands that create a session from its config
+    ///
+    /// Covers everything from `new-session` up to selecting the startup window.
+    fn session_plan(
+        &self,
+        session_name: &str,
+        config: &Config,
+        root_path: &Path,
+        startup_target: Option<(usize, Option<usize>)>,
+    ) -> Result<Vec<TmuxCommand>> {
+        let mut plan = vec![
+            TmuxCommand::new_session_command(session_name, root_path),
+            // Set 0-based indexing for both windows and panes (affects future windows/panes)
+            TmuxCommand::set_base_index_command(session_name),
+            TmuxCommand::set_pane_base_index_command(session_name),
+            // The initial window was created with the server's base-index; move it to 0
+            TmuxCommand::renumber_windows_command(session_name),
+        ];
+
+        // Create windows. Each is targeted by its index rather than its name,
+        // since names can repeat or look like an index.
+        let pre_window = SessionManager::pre_window_commands(config);
+        let mut window_index = 0;
+        for (index, window_config) in config.windows.iter().enumerate() {
+            for (window_name, content) in SessionManager::window_entries(index, window_config) {
+                plan.extend(SessionManager::window_plan(
+                    session_name,
+                    &window_name,
+                    window_index,
+                    content,
+                    root_path,
+                    &pre_window,
+                    window_index == 0,
+                )?);
+                window_index += 1;
+            }
+        }
+
+        // Land on the configured window and pane instead of the last one created,
+        // again by index now that the windows are numbered from 0
+        if let Some((window_index, pane_index)) = startup_target {
+            let window_target = window_index.to_string();
+            plan.push(TmuxCommand::select_window_command(
+                session_name,
+                &window_target,
+            ));
+            if let Some(pane_index) = pane_index {
+                plan.push(TmuxCommand::select_pane_command(
+                    session_name,
+                    &window_target,
+                    pane_index,
+                ));
+            }
+        }
+
+        // on_project_exit runs inside tmux whenever a client detaches
+        if let Some(hook) = &config.on_project_exit {
+            let commands = hook.commands();
+            if !commands.is_empty() {
+                let script = format!(
+                    "cd {} 2>/dev/null; {}",
+                    TmuxCommand::quote(&root_path.to_string_lossy()),
+                    commands.join("; ")
+                );
+                plan.push(TmuxCommand::set_hook_command(
+                    session_name,
+                    "client-detached",
+                    &format!("run-shell {}", TmuxCommand::quote(&script)),
+                ));
+            }
+        }
+
+        Ok(plan.into_iter().map(|cmd| self.on_socket(cmd)).collect())
+    }
+
+    /// Point a command at this manager's tmux socket, if one is set
+    fn on_socket(&self, cmd: TmuxCommand) -> TmuxCommand {
+        match &self.socket_path {
+            Some(socket) => cmd.socket(socket),
+            None => cmd,
+        }
+    }
+
+    /// Run the commands of a plan in order as a single tmux invocation
+    ///
+    /// tmux stops at the first failing command, like running them one by one.
+    fn execute_plan(&self, plan: Vec<TmuxCommand>) -> Result<()> {
+        if plan.is_empty() {
+            return Ok(());
+        }
+        self.execute(TmuxCommand::chain(plan))?;
+        Ok(())
+    }
+
+    /// Run a tmux command on this manager's socket
+    fn execute(&self, cmd: TmuxCommand) -> Result<String> {
+        self.backend.execute(self.on_socket(cmd))
+    }
+
+    /// Run an interactive tmux command such as `attach-session`
+    fn execute_interactive(&self, cmd: TmuxCommand) -> Result<()> {
+        self.backend.execute_interactive(self.on_socket(cmd))
+    }
+
+    /// Check whether a session is running on this manager's tmux server
+    fn session_exists(&self, session_name: &str) -> Result<bool> {
+        match self.execute(TmuxCommand::has_session_command(session_name)) {
+            Ok(_) => Ok(true),
+            Err(TmuxrsError::TmuxError(_)) => Ok(false), // Session doesn't exist
+            Err(e) => Err(e),                            // Other error
+        }
+    }
+
+    /// Load the configuration for a session, optionally from a custom config directory
+    fn load_config(&self, session_name: &str, config_dir: Option<&Path>) -> Result<Config> {
+        if let Some(config) = SessionManager::load_worktree_config(session_name, config_dir)? {
+            return Ok(config);
+        }
+
+        if let Some(config_dir) = config_dir {
+            // Load from custom config directory
+            let config_file = Config::config_file_in(config_dir, session_name)
+                .unwrap_or_else(|| config_dir.join(format!("{session_name}.yml")));
+            Config::parse_file(&config_file)
+        } else {
+            Config::load(session_name)
+        }
+    }
+
+    /// Load the configuration for a session if a config file exists for it
+    fn load_optional_config(
+        &self,
+        session_name: &str,
+        config_dir: Option<&Path>,
+    ) -> Result<Option<Config>> {
+        match SessionManager::existing_config_file(session_name, config_dir)? {
+            Some(config_file) => Config::parse_file(&config_file).map(Some),
+            None => SessionManager::load_worktree_config(session_name, config_dir),
+        }
+    }
+
+    /// Run the commands of a lifecycle hook in order from the project root
+    ///
+    /// Stops at the first failing command and reports it as `HookFailed`. The
+    /// backend runs each command, so a dry run records them instead.
+    fn run_hook(&self, hook_name: &str, hook: Option<&Hook>, root_path: &Path) -> Result<()> {
+        let Some(hook) = hook else {
+            return Ok(());
+        };
+
+        for command in hook.commands() {
+            self.backend.run_hook(hook_name, command, root_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the configured windows to an already running session
+    ///
+    /// Windows whose names already exist in the session are skipped rather than
+    /// duplicated, so appending the same config twice is harmless. New windows
+    /// are created at the indexes after the highest one in use and targeted by
+    /// them, since names can look like an index or contain `.`.
+    fn append_to_session(
+        &self,
+        session_name: &str,
+        config: &Config,
+        attach: bool,
+    ) -> Result<String> {
+        let root_path = SessionManager::root_path(config);
+        SessionManager::validate_layouts(config)?;
+
+        let pre_window = SessionManager::pre_window_commands(config);
+        let windows = SessionSnapshot::parse_windows(&self.execute(
+            TmuxCommand::list_windows_command(session_name, WINDOW_FORMAT),
+        )?)?;
+        let mut window_index = windows.iter().map(|window| window.index + 1).max();
+        let mut existing: Vec<String> = windows.into_iter().map(|window| window.name).collect();
+        let mut added = Vec::new();
+        let mut skipped = Vec::new();
+        let mut plan = Vec::new();
+
+        for (index, window_config) in config.windows.iter().enumerate() {
+            for (window_name, content) in SessionManager::window_entries(index, window_config) {
+                if existing.contains(&window_name) {
+                    skipped.push(window_name);
+                    continue;
+                }
+
+                let index = window_index.unwrap_or_default();
+                plan.extend(SessionManager::window_plan(
+                    session_name,
+                    &window_name,
+                    index,
+                    content,
+                    &root_path,
+                    &pre_window,
+                    false,
+                )?);
+                window_index = Some(index + 1);
+                existing.push(window_name.clone());
+                added.push(window_name);
+            }
+        }
+        self.execute_plan(plan)?;
+
+        let summary = SessionManager::format_append_summary(session_name, &added, &skipped);
+
+        if attach {
+            match self.execute_interactive(TmuxCommand::attach_session_command(session_name)) {
+                Ok(()) => Ok(summary),
+                Err(err) => Err(TmuxrsError::TmuxError(format!(
+                    "{summary} but failed to attach: {err}"
+                ))),
+            }
+        } else {
+            Ok(summary)
+        }
+    }
+
+    /// Start a session detecting name from directory
+    #[allow(dead_code)]
+    pub fn start_session_from_directory(
+        &self,
+        directory: &Path,
+        config_dir: Option<&Path>,
+    ) -> Result<String> {
+        let session_name = self.detect_session_name(directory, config_dir)?;
+        self.start_session(Some(&session_name), config_dir)
+    }
+
+    /// The session to start: `name` if given, otherwise the one detected from
+    /// the current directory
+    fn resolve_session_name(
+        &self,
+        name: Option<&str>,
+        config_dir: Option<&Path>,
+    ) -> Result<String> {
+        match name {
+            Some(name) => Ok(name.to_string()),
+            None => self.detect_session_name(&std::env::current_dir()?, config_dir),
+        }
+    }
+
+    /// The session for a directory: the config whose `root` contains it, or
+    /// else the name of its git repository or the directory itself
+    ///
+    /// Configs rooted at the home directory or `/` never match by root, so
+    /// they cannot take over a project that has a config of its own name.
+    ///
+    /// In a linked worktree, the project is found from the same place in the
+    /// main working tree and the session is `<project>@<worktree>`.
+    pub fn detect_session_name(
+        &self,
+        directory: &Path,
+        config_dir: Option<&Path>,
+    ) -> Result<String> {
+        let files = self.list_configs(config_dir)?;
+        if let Some(name) = SessionManager::config_for_directory(&files, directory)? {
+            return Ok(name);
+        }
+
+        if let Some(repo) =
+            GitRepository::discover(directory).filter(|repo| repo.is_linked_worktree())
+        {
+            let config_name = match repo.main_root() {
+                Some(main_root) => {
+                    let relative = directory.strip_prefix(&repo.root).unwrap_or(Path::new(""));
+                    SessionManager::config_for_directory(&files, &main_root.join(relative))?
+                }
+                None => None,
+            };
+            if let Some(config_name) = config_name.or_else(|| repo.name()) {
+                return Ok(SessionManager::worktree_session_name(
+                    &config_name,
+                    &repo.root,
+                ));
+            }
+        }
+        Config::detect_session_name(Some(directory))
+    }
+
+    /// Path of an existing config file, in `config_dir` or the first directory of the search path
+    pub fn config_file(&self, name: &str, config_dir: Option<&Path>) -> Result<PathBuf> {
+        match SessionManager::existing_config_file(name, config_dir)? {
+            Some(config_file) => Ok(config_file),
+            None => Err(TmuxrsError::ConfigNotFound(
+                SessionManager::config_file_path(name, config_dir)?
+                    .display()
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Write a commented template config for a new project rooted at `root`
+    ///
+    /// Refuses to replace an existing config. Returns the path of the new file.
+    pub fn create_config(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+        root: &Path,
+    ) -> Result<PathBuf> {
+        let config_file = SessionManager::config_file_path(name, config_dir)?;
+        if config_file.exists() {
+            return Err(TmuxrsError::ConfigExists(config_file.display().to_string()));
+        }
+
+        if let Some(parent) = config_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&config_file, Config::template(name, root))?;
+        Ok(config_file)
+    }
+
+    /// Copy a config to a new name, setting the `name` field of the copy
+    ///
+    /// Refuses to replace an existing config, or to write the config of a
+    /// running session, unless `force` is set.
+    pub fn copy_config(
+        &self,
+        source: &str,
+        destination: &str,
+        config_dir: Option<&Path>,
+        force: bool,
+    ) -> Result<String> {
+        let source_file = self.config_file(source, config_dir)?;
+        let destination_file = self.writable_config_file(destination, config_dir, force)?;
+
+        let content = std::fs::read_to_string(&source_file)?;
+        std::fs::write(&destination_file, Config::with_name(&content, destination))?;
+        Ok(format!(
+            "Copied '{source}' to '{destination}' ({})",
+            destination_file.display()
+        ))
+    }
+
+    /// Delete a config, unless its session is running and `force` is not set
+    pub fn delete_config(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+        force: bool,
+    ) -> Result<String> {
+        let config_file = self.config_file(name, config_dir)?;
+        self.ensure_not_running(name, force)?;
+
+        std::fs::remove_file(&config_file)?;
+        Ok(format!(
+            "Deleted configuration '{name}' ({})",
+            config_file.display()
+        ))
+    }
+
+    /// Rename a config, setting its `name` field to the new name
+    ///
+    /// Neither the old nor the new session may be running unless `force` is set,
+    /// since the running session would no longer match its config.
+    pub fn rename_config(
+        &self,
+        old_name: &str,
+        new_name: &str,
+        config_dir: Option<&Path>,
+        force: bool,
+    ) -> Result<String> {
+        let old_file = self.config_file(old_name, config_dir)?;
+        self.ensure_not_running(old_name, force)?;
+        let new_file = self.writable_config_file(new_name, config_dir, force)?;
+
+        let content = std::fs::read_to_string(&old_file)?;
+        std::fs::write(&new_file, Config::with_name(&content, new_name))?;
+        if old_file != new_file {
+            std::fs::remove_file(&old_file)?;
+        }
+        Ok(format!(
+            "Renamed '{old_name}' to '{new_name}' ({})",
+            new_file.display()
+        ))
+    }
+
+    /// Summarize the valid configs among `files`, with a single `list-sessions`
+    /// call to tell which of them are running
+    pub fn summarize_configs(&self, files: &[ConfigFile]) -> Result<Vec<ConfigSummary>> {
+        let running: Vec<String> = self
+            .running_sessions()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let summaries = files
+            .iter()
+            .filter_map(|file| Some((file, file.config.as_ref().ok()?)))
+            .map(|(file, config)| ConfigSummary {
+                name: config.name.clone(),
+                root: SessionManager::root_path(config),
+                windows: config.windows.len(),
+                config_file: file.path.clone(),
+                source_dir: file
+                    .path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                shadowed_by: file.shadowed_by.clone(),
+                running: file.shadowed_by.is_none()
+                    && file
+                        .path
+                        .file_stem()
+                        .is_some_and(|stem| running.iter().any(|name| stem == name.as_str())),
+            })
+            .collect();
+        Ok(summaries)
+    }
+
+    /// Path to write a config to, checking that doing so will not clobber anything
+    fn writable_config_file(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+        force: bool,
+    ) -> Result<PathBuf> {
+        if name.trim().is_empty() || name.contains('/') {
+            return Err(TmuxrsError::InvalidConfig(format!(
+                "Invalid configuration name '{name}'"
+            )));
+        }
+
+        let config_file = SessionManager::config_file_path(name, config_dir)?;
+        if config_file.exists() && !force {
+            return Err(TmuxrsError::ConfigExists(config_file.display().to_string()));
+        }
+        self.ensure_not_running(name, force)?;
+
+        if let Some(parent) = config_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(config_file)
+    }
+
+    /// Refuse to change the config of a running session unless `force` is set
+    fn ensure_not_running(&self, name: &str, force: bool) -> Result<()> {
+        if !force && self.session_exists(name)? {
+            return Err(TmuxrsError::SessionRunning(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// List the config files of `config_dir`, or of every directory in the
+    /// search path, with each file's config or parse error
+    ///
+    /// Invalid and shadowed files are included rather than skipped, so callers
+    /// can report them; see `load_configs`.
+    pub fn list_configs(&self, config_dir: Option<&Path>) -> Result<Vec<ConfigFile>> {
+        let search_dirs = match config_dir {
+            Some(dir) => vec![dir.to_path_buf()],
+            None => Config::config_dirs()?,
+        };
+        SessionManager::load_configs(&search_dirs)
+    }
+
+    /// Stop a session
+    #[allow(dead_code)]
+    pub fn stop_session(&self, name: &str) -> Result<String> {
+        self.stop_session_with_options(name, None)
+    }
+
+    /// Stop a session, running its `on_project_stop` hook from the given config directory
+    pub fn stop_session_with_options(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+    ) -> Result<String> {
+        // Check if session exists first
+        if !self.session_exists(name)? {
+            return Err(TmuxrsError::TmuxError(format!(
+                "Session '{name}' does not exist"
+            )));
+        }
+
+        // A broken or missing config must not prevent stopping the session
+        let config = self.load_optional_config(name, config_dir).ok().flatten();
+
+        self.execute(TmuxCommand::kill_session_command(name))?;
+
+        if let Some(config) = &config {
+            let root_path = SessionManager::root_path(config);
+            self.run_hook(
+                "on_project_stop",
+                config.on_project_stop.as_ref(),
+                &root_path,
+            )?;
+        }
+
+        Ok(format!("Stopped session '{name}'"))
+    }
+
+    /// Names and working directories of the sessions on the server
+    fn running_sessions(&self) -> Result<Vec<(String, PathBuf)>> {
+        // Without a server there are no sessions to list
+        match self.execute(TmuxCommand::list_sessions_command(SESSION_FORMAT)) {
+            Ok(output) => Ok(SessionSnapshot::parse_sessions(&output)),
+            Err(TmuxrsError::TmuxError(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Every session on this manager's tmux server, with its clients, windows and age
+    pub fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
+        match self.execute(TmuxCommand::list_sessions_command(SESSION_INFO_FORMAT)) {
+            Ok(output) => SessionInfo::parse_list(&output),
+            Err(TmuxrsError::TmuxError(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The working trees of the repository a config's root is in, with the
+    /// session each one gets and whether it is running
+    ///
+    /// Worktrees that would share a session are reported as `AmbiguousWorktree`.
+    pub fn worktree_sessions(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+    ) -> Result<Vec<WorktreeSession>> {
+        let config = self.load_config(name, config_dir)?;
+        let repo = SessionManager::config_repository(name, &config)?;
+        let running: Vec<String> = self
+            .list_sessions()?
+            .into_iter()
+            .map(|session| session.name)
+            .collect();
+
+        let worktrees = repo.worktrees();
+        let sessions: Vec<String> = worktrees
+            .iter()
+            .map(|worktree| {
+                if worktree.is_main {
+                    name.to_string()
+                } else {
+                    SessionManager::worktree_session_name(name, &worktree.path)
+                }
+            })
+            .collect();
+        if let Some(session) = sessions
+            .iter()
+            .enumerate()
+            .find_map(|(index, session)| sessions[..index].contains(session).then_some(session))
+        {
+            let sharing: Vec<Worktree> = worktrees
+                .iter()
+                .zip(&sessions)
+                .filter(|(_, other)| *other == session)
+                .map(|(worktree, _)| worktree.clone())
+                .collect();
+            return Err(SessionManager::ambiguous_worktree(session, &sharing));
+        }
+
+        Ok(worktrees
+            .into_iter()
+            .zip(sessions)
+            .map(|(worktree, session)| WorktreeSession {
+                running: running.contains(&session),
+                session,
+                path: worktree.path,
+            })
+            .collect())
+    }
+
+    /// Capture the windows, layouts, pane directories and programs of a running session
+    ///
+    /// Programs get their full command line from `TmuxBackend::command_line`
+    /// where it can be found. With `scrollback`, the history and visible
+    /// contents of every pane are captured as well.
+    pub fn snapshot_session(&self, name: &str, scrollback: bool) -> Result<SessionSnapshot> {
+        let root = self
+            .running_sessions()?
+            .into_iter()
+            .find(|(session, _)| session == name)
+            .map(|(_, root)| root)
+            .ok_or_else(|| TmuxrsError::TmuxError(format!("Session '{name}' does not exist")))?;
+
+        let windows = self.execute(TmuxCommand::list_windows_command(name, WINDOW_FORMAT))?;
+        let mut windows = SessionSnapshot::parse_windows(&windows)?;
+
+        // One invocation lists the panes of every window
+        let panes = self.execute(TmuxCommand::chain(windows.iter().map(|window| {
+            TmuxCommand::list_panes_command(name, &window.index.to_string(), PANE_FORMAT)
+        })))?;
+        SessionSnapshot::add_panes(&mut windows, &panes)?;
+        for pane in windows.iter_mut().flat_map(|window| &mut window.panes) {
+            if let (Some(pid), Some(program)) = (pane.pid, &pane.command) {
+                pane.command_line = self.backend.command_line(pid, program);
+            }
+        }
+
+        if scrollback {
+            for window in &mut windows {
+                let window_index = window.index.to_string();
+                for pane in &mut window.panes {
+                    pane.scrollback = Some(self.execute(TmuxCommand::capture_pane_command(
+                        name,
+                        &window_index,
+                        pane.index,
+                    ))?);
+                }
+            }
+        }
+
+        Ok(SessionSnapshot {
+            name: name.to_string(),
+            root,
+            windows,
+        })
+    }
+
+    /// Save a running session as a config file that `start` can load back
+    ///
+    /// With `with_commands`, each pane runs the program it was running. An
+    /// existing config is only replaced when `force` is set.
+    pub fn freeze_session(
+        &self,
+        name: &str,
+        config_dir: Option<&Path>,
+        force: bool,
+        with_commands: bool,
+    ) -> Result<String> {
+        let config_file = SessionManager::config_file_path(name, config_dir)?;
+        if config_file.exists() && !force {
+            return Err(TmuxrsError::ConfigExists(config_file.display().to_string()));
+        }
+
+        let yaml = self
+            .snapshot_session(name, false)?
+            .to_frozen_yaml(with_commands)?;
+        if let Some(parent) = config_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&config_file, yaml)?;
+
+        Ok(format!(
+            "Saved session '{name}' to {}",
+            config_file.display()
+        ))
+    }
+
+    /// Save the live state of every running session that has a config
+    ///
+    /// The state goes to `state_file`, or `SavedState::default_path` by
+    /// default. Nothing is written when no such session is running, so an
+    /// earlier save is not replaced by an empty one.
+    pub fn save_sessions(
+        &self,
+        config_dir: Option<&Path>,
+        state_file: Option<&Path>,
+        scrollback: bool,
+    ) -> Result<String> {
+        let mut names = Vec::new();
+        for (name, _) in self.running_sessions()? {
+            if SessionManager::existing_config_file(&name, config_dir)?.is_some() {
+                names.push(name);
+            }
+        }
+        if names.is_empty() {
+            return Ok("No sessions with a configuration are running".to_string());
+        }
+
+        let sessions = names
+            .iter()
+            .map(|name| self.snapshot_session(name, scrollback))
+            .collect::<Result<Vec<_>>>()?;
+        let state_file = match state_file {
+            Some(state_file) => state_file.to_path_buf(),
+            None => SavedState::default_path()?,
+        };
+        SavedState::new(sessions).write(&state_file)?;
+
+        Ok(format!(
+            "Saved {} session(s) to {}: {}",
+            names.len(),
+            state_file.display(),
+            names.join(", ")
+        ))
+    }
+
+    /// Recreate the sessions saved by `save_sessions`
+    ///
+    /// Sessions that are already running are left alone. Captured scrollback is
+    /// written next to the state file and printed in its pane. With
+    /// `start_commands`, each program is started again from its saved command
+    /// line. Programs that are not started, including those whose command line
+    /// was not known, are listed in the summary instead of being run by name.
+    pub fn restore_sessions(
+        &self,
+        state_file: Option<&Path>,
+        start_commands: bool,
+    ) -> Result<String> {
+        let state_file = match state_file {
+            Some(state_file) => state_file.to_path_buf(),
+            None => SavedState::default_path()?,
+        };
+        let state = SavedState::load(&state_file)?;
+        let scrollback_dir = state_file.with_extension("scrollback");
+
+        let mut restored = Vec::new();
+        let mut skipped = Vec::new();
+        let mut programs = Vec::new();
+        for snapshot in &state.sessions {
+            if self.session_exists(&snapshot.name)? {
+                skipped.push(snapshot.name.clone());
+                continue;
+            }
+
+            let config = snapshot.to_restore_config(&scrollback_dir, start_commands)?;
+            let root_path = SessionManager::root_path(&config);
+            SessionManager::validate_layouts(&config)?;
+
+            let plan = self.session_plan(&snapshot.name, &config, &root_path, None)?;
+            self.execute_plan(plan)?;
+            restored.push(snapshot.name.clone());
+            programs.extend(
+                snapshot
+                    .unstarted_programs(start_commands)
+                    .into_iter()
+                    .map(|program| format!("{}: {program}", snapshot.name)),
+            );
+        }
+
+        let mut summary = format!("Restored {} session(s)", restored.len());
+        if !restored.is_empty() {
+            summary.push_str(&format!(": {}", restored.join(", ")));
+        }
+        if !skipped.is_empty() {
+            summary.push_str(&format!(
+                "; skipped running session(s): {}",
+                skipped.join(", ")
+            ));
+        }
+        if !programs.is_empty() {
+            summary.push_str("\nPrograms that were running, to start again by hand:");
+            for program in &programs {
+                summary.push_str(&format!("\n  {program}"));
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// Helpers that only depend on the config, shared by every backend
+impl SessionManager {
+    /// Load the config files of every directory of `search_dirs` that exists,
+    /// with each file's config or parse error
+    ///
+    /// Files are listed in order of precedence; a file whose name was already
+    /// found in an earlier directory is marked as shadowed.
+    pub fn load_configs(search_dirs: &[PathBuf]) -> Result<Vec<ConfigFile>> {
+        let mut files: Vec<ConfigFile> = Vec::new();
+        for search_dir in search_dirs.iter().filter(|dir| dir.is_dir()) {
+            for path in Self::config_files(search_dir)? {
+                let mut file = ConfigFile::load(&path);
+                file.shadowed_by = files
+                    .iter()
+                    .find(|earlier| {
+                        earlier.shadowed_by.is_none()
+                            && earlier.path.file_stem() == path.file_stem()
+                    })
+                    .map(|earlier| earlier.path.clone());
+                files.push(file);
+            }
+        }
+        Ok(files)
+    }
+
+    /// The `.yml` and `.yaml` files of a config directory, sorted by name
+    ///
+    /// A `.yml` file comes before a `.yaml` file of the same name, since it is
+    /// the one that gets loaded.
+    pub fn config_files(config_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = Self::visible_files(config_dir)?
+            .into_iter()
+            .filter(|path| Self::is_config_file(path))
+            .collect();
+        files.sort_by(|a, b| {
+            (a.file_stem(), a.extension() != Some("yml".as_ref()))
+                .cmp(&(b.file_stem(), b.extension() != Some("yml".as_ref())))
+        });
+        Ok(files)
+    }
+
+    /// The files of a config directory that `config_files` leaves out for not
+    /// being `.yml` or `.yaml` files, sorted by name
+    pub fn ignored_files(config_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = Self::visible_files(config_dir)?
+            .into_iter()
+            .filter(|path| !Self::is_config_file(path))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// The files of a directory that are not hidden, like the settings file,
+    /// since hidden files are never configs
+    fn visible_files(config_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(config_dir)? {
+            let path = entry?.path();
+            if path.is_file() && !Self::is_hidden(&path) {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    fn is_hidden(path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    }
+
+    /// Whether a file is a session config: a `.yml` or `.yaml` file that is
+    /// not hidden, like the settings file
+    pub fn is_config_file(path: &Path) -> bool {
+        !Self::is_hidden(path)
+            && path
+                .extension()
+                .is_some_and(|ext| ext == "yml" || ext == "yaml")
+    }
+
+    /// The existing config file for a session, in `config_dir` or the first
+    /// directory of the search path that has one
+    fn existing_config_file(
+        session_name: &str,
+        config_dir: Option<&Path>,
+    ) -> Result<Option<PathBuf>> {
+        match config_dir {
+            Some(config_dir) => Ok(Config::config_file_in(config_dir, session_name)),
+            None => Config::find_config_file(session_name),
+        }
+    }
+
+    /// Path where the config file for a session is written, in `config_dir` or the tmuxrs directory
+    fn config_file_path(session_name: &str, config_dir: Option<&Path>) -> Result<PathBuf> {
+        match config_dir {
+            Some(config_dir) => Ok(config_dir.join(format!("{session_name}.yml"))),
+            None => Config::get_config_file_path(session_name),
+        }
+    }
+
+    /// The config whose expanded `root` contains `directory`, by name
+    ///
+    /// When several do, the deepest root wins; roots of the same depth are
+    /// ambiguous. Roots at the home directory or `/` contain nearly every
+    /// project, so those configs are left to match by name like configs
+    /// without a `root`. Invalid configs and shadowed files are not considered.
+    pub fn config_for_directory(files: &[ConfigFile], directory: &Path) -> Result<Option<String>> {
+        let directory =
+            std::fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf());
+        let home = dirs::home_dir().map(|home| std::fs::canonicalize(&home).unwrap_or(home));
+
+        let mut matches: Vec<(usize, String)> = Vec::new();
+        for file in files.iter().filter(|file| file.shadowed_by.is_none()) {
+            let Ok(config) = &file.config else { continue };
+            let (Some(root), Some(name)) = (&config.root, file.path.file_stem()) else {
+                continue;
+            };
+            let root = expand_path(root);
+            let root = std::fs::canonicalize(&root).unwrap_or(root);
+            if root.parent().is_none() || Some(&root) == home.as_ref() {
+                continue;
+            }
+            if directory.starts_with(&root) {
+                matches.push((
+                    root.components().count(),
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+        }
+
+        let Some(deepest) = matches.iter().map(|(depth, _)| *depth).max() else {
+            return Ok(None);
+        };
+        let mut names: Vec<String> = matches
+            .into_iter()
+            .filter(|(depth, _)| *depth == deepest)
+            .map(|(_, name)| name)
+            .collect();
+        if names.len() > 1 {
+            return Err(TmuxrsError::AmbiguousConfig {
+                directory: directory.display().to_string(),
+                names,
+            });
+        }
+        Ok(names.pop())
+    }
+
+    /// The session for a linked worktree of a config's repository: `<config>@<worktree>`
+    ///
+    /// The worktree is named after its directory, with the `.` and `:` that
+    /// tmux does not allow in session names replaced by `-`, and so is any `@`,
+    /// so that the worktree name is everything after the last `@`.
+    pub fn worktree_session_name(config_name: &str, worktree: &Path) -> String {
+        let worktree_name = worktree
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+            .replace(['.', ':', WORKTREE_SEPARATOR], "-");
+        format!("{config_name}{WORKTREE_SEPARATOR}{worktree_name}")
+    }
+
+    /// Split a worktree session name into its config and worktree names
+    ///
+    /// Splits at the last `@`, since config names may contain one but
+    /// worktree names do not.
+    pub fn split_worktree_session_name(session_name: &str) -> Option<(&str, &str)> {
+        session_name
+            .rsplit_once(WORKTREE_SEPARATOR)
+            .filter(|(config_name, worktree)| !config_name.is_empty() && !worktree.is_empty())
+    }
+
+    /// The config for a worktree session `<config>@<worktree>`, with its root
+    /// moved from the config's working tree to the same place in the worktree
+    ///
+    /// `None` when the name is not a worktree session name, when a config file
+    /// has exactly that name, or when there is no config to base it on.
+    /// Worktrees whose names only differ in the characters that are replaced
+    /// would share the session, which is reported as `AmbiguousWorktree`.
+    fn load_worktree_config(
+        session_name: &str,
         config_dir: Option<&Path>,
-        attach: bool,
-        append: bool,
-    ) -> Result<String> {
-        let session_name = match name {
-            Some(n) => n.to_string(),
-            None => Config::detect_session_name(None)?,
+    ) -> Result<Option<Config>> {
+        let Some((config_name, worktree_name)) = Self::split_worktree_session_name(session_name)
+        else {
+            return Ok(None);
+        };
+        if Self::existing_config_file(session_name, config_dir)?.is_some() {
+            return Ok(None);
+        }
+        let Some(config_file) = Self::existing_config_file(config_name, config_dir)? else {
+            return Ok(None);
         };
 
-        // Check if session already exists
-        if TmuxCommand::session_exists_with_socket(&session_name, self.socket_path.as_ref())? {
-            if append {
-                // TODO: Implement append functionality in Phase 2
-                return Err(TmuxrsError::TmuxError(
-                    "Append functionality not yet implemented".to_string(),
-                ));
-            } else if attach {
-                // Attach to existing session
-                match TmuxCommand::attach_session_with_socket(
-                    &session_name,
-                    self.socket_path.as_ref(),
-                ) {
-                    Ok(()) => {
-                        // This line should never be reached in practice because
-                        // successful attach takes over the terminal process
-                        return Ok(format!("Attached to existing session '{session_name}'"));
-                    }
-                    Err(err) => {
-                        // Attach failed - could be no TTY, session doesn't exist, etc.
-                        return Err(TmuxrsError::TmuxError(format!(
-                            "Failed to attach to session '{session_name}': {err}"
-                        )));
-                    }
-                }
-            } else {
-                return Ok(format!("Session '{session_name}' already exists"));
-            }
+        let mut config = Config::parse_file(&config_file)?;
+        let repo = Self::config_repository(config_name, &config)?;
+        let mut worktrees: Vec<Worktree> = repo
+            .worktrees()
+            .into_iter()
+            .filter(|worktree| {
+                !worktree.is_main
+                    && Self::worktree_session_name(config_name, &worktree.path) == session_name
+            })
+            .collect();
+        if worktrees.len() > 1 {
+            return Err(Self::ambiguous_worktree(session_name, &worktrees));
         }
+        let worktree = worktrees
+            .pop()
+            .ok_or_else(|| TmuxrsError::WorktreeNotFound {
+                name: config_name.to_string(),
+                worktree: worktree_name.to_string(),
+            })?;
 
-        // Load configuration
-        let config = if let Some(config_dir) = config_dir {
-            // Load from custom config directory
-            let config_file = config_dir.join(format!("{session_name}.yml"));
-            Config::parse_file(&config_file)?
-        } else {
-            Config::load(&session_name)?
+        let root_path = Self::root_path(&config);
+        let worktree_root = match root_path.strip_prefix(&repo.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => worktree.path.join(relative),
+            _ => worktree.path,
         };
+        config.root = Some(worktree_root.display().to_string());
+        Ok(Some(config))
+    }
+
+    /// The error for several worktrees that would share a session
+    fn ambiguous_worktree(session_name: &str, worktrees: &[Worktree]) -> TmuxrsError {
+        TmuxrsError::AmbiguousWorktree {
+            session: session_name.to_string(),
+            paths: worktrees
+                .iter()
+                .map(|worktree| worktree.path.display().to_string())
+                .collect(),
+        }
+    }
 
-        // Create session
-        let root_dir = config.root.as_deref().unwrap_or("~");
-        let root_path = Self::expand_path(root_dir)?;
-        TmuxCommand::new_session_with_socket(&session_name, &root_path, self.socket_path.as_ref())?;
+    /// The git repository a config's root is in
+    fn config_repository(name: &str, config: &Config) -> Result<GitRepository> {
+        let root_path = Self::root_path(config);
+        GitRepository::discover(&root_path).ok_or_else(|| TmuxrsError::NotInGitRepository {
+            name: name.to_string(),
+            root: root_path.display().to_string(),
+        })
+    }
 
-        // Set 0-based indexing for both windows and panes (affects future windows/panes)
-        TmuxCommand::set_base_index_with_socket(&session_name, self.socket_path.as_ref())?;
-        TmuxCommand::set_pane_base_index_with_socket(&session_name, self.socket_path.as_ref())?;
+    /// Resolve the expanded session root of a config, defaulting to the home directory
+    fn root_path(config: &Config) -> PathBuf {
+        expand_path(config.root.as_deref().unwrap_or("~"))
+    }
 
-        // Create windows
-        for (index, window_config) in config.windows.iter().enumerate() {
-            match window_config {
-                crate::config::WindowConfig::Simple(command) => {
-                    let window_name = format!("window-{index}");
-
-                    if index == 0 {
-                        // Dynamically detect the initial window index (may vary by tmux version/config)
-                        let initial_window_index = TmuxCommand::get_first_window_index_with_socket(
-                            &session_name,
-                            self.socket_path.as_ref(),
-                        )?;
-                        TmuxCommand::rename_window_with_socket(
-                            &session_name,
-                            &initial_window_index,
-                            &window_name,
-                            self.socket_path.as_ref(),
-                        )?;
-                    } else {
-                        // Create additional windows (these will use 0-based indexing since base-index is set)
-                        TmuxCommand::new_window_with_socket(
-                            &session_name,
-                            &window_name,
-                            None, // No command - let shell initialize properly
-                            Some(&root_path),
-                            self.socket_path.as_ref(),
-                        )?;
-                    }
+    /// Resolve `startup_window` and `startup_pane` to a window position and tmux pane index
+    ///
+    /// Returns `None` when neither is set. Windows are counted in creation order,
+    /// and a name picks the first window that has it. `startup_pane` is a
+    /// position in the window's `panes`, mapped to the index tmux gives that
+    /// pane once splits with a `target` are done. A `startup_pane` without a
+    /// `startup_window` applies to the first window. Invalid targets are
+    /// reported before anything is created.
+    fn startup_target(config: &Config) -> Result<Option<(usize, Option<usize>)>> {
+        if config.startup_window.is_none() && config.startup_pane.is_none() {
+            return Ok(None);
+        }
 
-                    // Send command to the window
-                    if !command.trim().is_empty() {
-                        TmuxCommand::send_keys_with_socket(
-                            &session_name,
-                            &window_name,
-                            command,
-                            self.socket_path.as_ref(),
-                        )?;
+        // (window name, tmux index of each configured pane) in creation order
+        let windows: Vec<(String, Vec<usize>)> = config
+            .windows
+            .iter()
+            .enumerate()
+            .flat_map(|(index, window_config)| Self::window_entries(index, window_config))
+            .map(|(name, content)| {
+                let positions = match content {
+                    WindowContent::Command(_) => vec![0],
+                    WindowContent::Layout(layout_config) => {
+                        Self::pane_positions(&name, &layout_config.panes)?.positions
                     }
-                }
-                crate::config::WindowConfig::Complex { window } => {
-                    for (window_index, (window_name, command)) in window.iter().enumerate() {
-                        if index == 0 && window_index == 0 {
-                            // Dynamically detect the initial window index (may vary by tmux version/config)
-                            let initial_window_index =
-                                TmuxCommand::get_first_window_index_with_socket(
-                                    &session_name,
-                                    self.socket_path.as_ref(),
-                                )?;
-                            TmuxCommand::rename_window_with_socket(
-                                &session_name,
-                                &initial_window_index,
-                                window_name,
-                                self.socket_path.as_ref(),
-                            )?;
-                        } else {
-                            // Create additional windows (use 0-based indexing)
-                            TmuxCommand::new_window_with_socket(
-                                &session_name,
-                                window_name,
-                                None, // No command - let shell initialize properly
-                                Some(&root_path),
-                                self.socket_path.as_ref(),
-                            )?;
-                        }
+                };
+                Ok((name, positions))
+            })
+            .collect::<Result<_>>()?;
 
-                        // Send command to the window
-                        if !command.trim().is_empty() {
-                            TmuxCommand::send_keys_with_socket(
-                                &session_name,
-                                window_name,
-                                command,
-                                self.socket_path.as_ref(),
-                            )?;
-                        }
-                    }
-                }
-                crate::config::WindowConfig::WithLayout { window } => {
-                    for (window_index, (window_name, layout_config)) in window.iter().enumerate() {
-                        if index == 0 && window_index == 0 {
-                            // Dynamically detect the initial window index (may vary by tmux version/config)
-                            let initial_window_index =
-                                TmuxCommand::get_first_window_index_with_socket(
-                                    &session_name,
-                                    self.socket_path.as_ref(),
-                                )?;
-                            TmuxCommand::rename_window_with_socket(
-                                &session_name,
-                                &initial_window_index,
-                                window_name,
-                                self.socket_path.as_ref(),
-                            )?;
-                        } else {
-                            // Create additional windows (use 0-based indexing)
-                            TmuxCommand::new_window_with_socket(
-                                &session_name,
-                                window_name,
-                                None, // No command - let shell initialize properly
-                                Some(&root_path),
-                                self.socket_path.as_ref(),
-                            )?;
-                        }
+        let window_index = match &config.startup_window {
+            Some(WindowTarget::Name(name)) => windows
+                .iter()
+                .position(|(window, _)| window == name)
+                .ok_or_else(|| {
+                    TmuxrsError::InvalidConfig(format!("startup_window '{name}' does not exist"))
+                })?,
+            Some(WindowTarget::Index(index)) if *index < windows.len() => *index,
+            Some(WindowTarget::Index(index)) => {
+                return Err(TmuxrsError::InvalidConfig(format!(
+                    "startup_window {index} is out of range ({} windows)",
+                    windows.len()
+                )))
+            }
+            None if !windows.is_empty() => 0,
+            None => {
+                return Err(TmuxrsError::InvalidConfig(
+                    "startup_pane set but no windows defined".to_string(),
+                ))
+            }
+        };
+        let (window_name, positions) = &windows[window_index];
 
-                        // Send first pane command if not empty
-                        let first_pane = layout_config.panes.first().ok_or_else(|| {
-                            TmuxrsError::TmuxError(
-                                "Window layout must have at least one pane".to_string(),
-                            )
-                        })?;
-                        if !first_pane.trim().is_empty() {
-                            // Use precise pane targeting for first pane (index 0)
-                            TmuxCommand::send_keys_to_pane_with_socket(
-                                &session_name,
-                                window_name,
-                                0, // First pane is always index 0 with 0-based indexing
-                                first_pane,
-                                self.socket_path.as_ref(),
-                            )?;
-                        }
+        let pane_index = config
+            .startup_pane
+            .map(|pane| {
+                positions.get(pane).copied().ok_or_else(|| {
+                    TmuxrsError::InvalidConfig(format!(
+                        "startup_pane {pane} is out of range (window '{window_name}' has {} panes)",
+                        positions.len()
+                    ))
+                })
+            })
+            .transpose()?;
 
-                        // Add additional panes by splitting
-                        for (pane_index, pane_command) in
-                            layout_config.panes.iter().skip(1).enumerate()
-                        {
-                            // Create split without command to allow proper shell initialization
-                            TmuxCommand::split_window_horizontal_with_socket(
-                                &session_name,
-                                window_name,
-                                "", // Empty command - shell will initialize properly
-                                Some(&root_path),
-                                self.socket_path.as_ref(),
-                            )?;
-
-                            // Send command to the new pane using precise pane targeting
-                            if !pane_command.trim().is_empty() {
-                                // With 0-based indexing: first pane is 0, second is 1, third is 2, etc.
-                                let target_pane_index = pane_index + 1; // +1 because we skipped the first pane
-                                TmuxCommand::send_keys_to_pane_with_socket(
-                                    &session_name,
-                                    window_name,
-                                    target_pane_index,
-                                    pane_command,
-                                    self.socket_path.as_ref(),
-                                )?;
-                            }
-                        }
+        Ok(Some((window_index, pane_index)))
+    }
 
-                        // Apply layout if specified
-                        if let Some(layout) = &layout_config.layout {
-                            TmuxCommand::select_layout_with_socket(
-                                &session_name,
-                                window_name,
-                                layout,
-                                self.socket_path.as_ref(),
-                            )?;
+    /// Check every window layout against the panes the window defines
+    ///
+    /// Runs before anything is created, so that a mistyped preset or a corrupted
+    /// custom layout string is reported up front instead of as a tmux error
+    /// halfway through building the session.
+    fn validate_layouts(config: &Config) -> Result<()> {
+        for (index, window_config) in config.windows.iter().enumerate() {
+            for (window_name, content) in Self::window_entries(index, window_config) {
+                let WindowContent::Layout(layout_config) = content else {
+                    continue;
+                };
+                if let Some(layout) = &layout_config.layout {
+                    Layout::validate(layout, layout_config.panes.len()).map_err(|source| {
+                        TmuxrsError::InvalidLayout {
+                            window: window_name.clone(),
+                            source,
                         }
-                    }
+                    })?;
                 }
             }
         }
+        Ok(())
+    }
 
-        // Handle attachment
-        if attach {
-            match TmuxCommand::attach_session_with_socket(&session_name, self.socket_path.as_ref())
-            {
-                Ok(()) => {
-                    // This line should never be reached in practice because
-                    // successful attach takes over the terminal process
-                    Ok(format!("Started and attached to session '{session_name}'"))
-                }
-                Err(err) => {
-                    // Attach failed - provide helpful error message
-                    Err(TmuxrsError::TmuxError(format!(
-                        "Started session '{session_name}' but failed to attach: {err}"
-                    )))
-                }
-            }
+    /// Resolve a window or pane `root` against the session root
+    ///
+    /// The root is expanded like the session root; relative results are joined
+    /// onto the session root.
+    fn resolve_root(root_path: &Path, root: &str) -> PathBuf {
+        let expanded = expand_path(root);
+        if expanded.is_absolute() {
+            expanded
         } else {
-            Ok(format!("Started detached session '{session_name}'"))
+            root_path.join(expanded)
         }
     }
 
-    /// Start a session detecting name from directory
-    #[allow(dead_code)]
-    pub fn start_session_from_directory(
-        &self,
-        directory: &Path,
-        config_dir: Option<&Path>,
-    ) -> Result<String> {
-        let session_name = Config::detect_session_name(Some(directory))?;
-        self.start_session(Some(&session_name), config_dir)
+    /// The directory a pane starts in: its own root if set, otherwise its window's
+    fn pane_root(root_path: &Path, window_root: &Path, pane: &PaneConfig) -> PathBuf {
+        match pane.root() {
+            Some(root) => Self::resolve_root(root_path, root),
+            None => window_root.to_path_buf(),
+        }
     }
 
-    /// List available configurations
-    pub fn list_configs(&self, config_dir: Option<&Path>) -> Result<Vec<Config>> {
-        let search_dir = match config_dir {
-            Some(dir) => dir.to_path_buf(),
-            None => {
-                let home_dir = dirs::home_dir().ok_or_else(|| {
-                    TmuxrsError::ConfigNotFound("Could not find home directory".to_string())
+    /// The session-level `pre_window` commands, sent to every pane before its command
+    fn pre_window_commands(config: &Config) -> Vec<&str> {
+        config
+            .pre_window
+            .as_ref()
+            .map(Hook::commands)
+            .unwrap_or_default()
+    }
+
+    /// Describe which windows an append added and which it skipped
+    fn format_append_summary(session_name: &str, added: &[String], skipped: &[String]) -> String {
+        let mut summary = format!(
+            "Appended {} window(s) to session '{session_name}'",
+            added.len()
+        );
+        if !added.is_empty() {
+            summary.push_str(&format!(" (added: {})", added.join(", ")));
+        }
+        if !skipped.is_empty() {
+            summary.push_str(&format!(
+                "; skipped existing window(s): {}",
+                skipped.join(", ")
+            ));
+        }
+        summary
+    }
+
+    /// Flatten a window entry from the config into (name, content) pairs
+    ///
+    /// Simple windows carry no name, so they are named after their position in the config.
+    fn window_entries(
+        index: usize,
+        window_config: &WindowConfig,
+    ) -> Vec<(String, WindowContent<'_>)> {
+        match window_config {
+            WindowConfig::Simple(command) => {
+                vec![(format!("window-{index}"), WindowContent::Command(command))]
+            }
+            WindowConfig::Complex { window } => window
+                .iter()
+                .map(|(name, command)| (name.clone(), WindowContent::Command(command)))
+                .collect(),
+            WindowConfig::WithLayout { window } => window
+                .iter()
+                .map(|(name, layout)| (name.clone(), WindowContent::Layout(layout)))
+                .collect(),
+        }
+    }
+
+    /// Build the commands that create a single window and populate it with its command or panes
+    ///
+    /// The window is created at `window_index` and targeted by it, since
+    /// names can repeat or look like an index. `pre_window` commands are sent
+    /// to every pane before its own command. When `reuse_initial` is true the
+    /// session's initial window, which must be at `window_index`, is renamed
+    /// instead of creating a new one.
+    fn window_plan(
+        session_name: &str,
+        window_name: &str,
+        window_index: usize,
+        content: WindowContent<'_>,
+        root_path: &Path,
+        pre_window: &[&str],
+        reuse_initial: bool,
+    ) -> Result<Vec<TmuxCommand>> {
+        let mut plan = Vec::new();
+        let window_target = &window_index.to_string();
+
+        let (window_root, first_pane) = match content {
+            WindowContent::Command(_) => (root_path.to_path_buf(), None),
+            WindowContent::Layout(layout_config) => {
+                let first_pane = layout_config.panes.first().ok_or_else(|| {
+                    TmuxrsError::TmuxError("Window layout must have at least one pane".to_string())
                 })?;
-                home_dir.join(".config").join("tmuxrs")
+                let window_root = match &layout_config.root {
+                    Some(root) => Self::resolve_root(root_path, root),
+                    None => root_path.to_path_buf(),
+                };
+                (window_root, Some(first_pane))
             }
         };
+        // The first pane comes with the window, so the window starts in its directory
+        let first_pane_root = match first_pane {
+            Some(pane) => Self::pane_root(root_path, &window_root, pane),
+            None => window_root.clone(),
+        };
+
+        if reuse_initial {
+            plan.push(TmuxCommand::rename_window_command(
+                session_name,
+                window_target,
+                window_name,
+            ));
 
-        if !search_dir.exists() {
-            return Ok(Vec::new());
+            // The initial window was opened in the session root; restart its shell elsewhere
+            if first_pane_root != root_path {
+                plan.push(TmuxCommand::respawn_pane_command(
+                    session_name,
+                    window_target,
+                    0,
+                    &first_pane_root,
+                ));
+            }
+        } else {
+            // Create additional windows at their index, which is free
+            plan.push(TmuxCommand::new_window_command(
+                &format!("{session_name}:{window_index}"),
+                window_name,
+                None, // No command - let shell initialize properly
+                Some(&first_pane_root),
+            ));
         }
 
-        let mut configs = Vec::new();
+        match content {
+            WindowContent::Command(command) => {
+                // Send pre_window commands and then the command to the window
+                Self::send_commands(
+                    &mut plan,
+                    session_name,
+                    window_target,
+                    None,
+                    pre_window,
+                    command,
+                );
+            }
+            WindowContent::Layout(layout_config) => {
+                // pre_window runs before the window's own pre commands in every pane
+                let mut pre_commands = pre_window.to_vec();
+                if let Some(pre) = &layout_config.pre {
+                    pre_commands.extend(pre.commands());
+                }
 
-        for entry in std::fs::read_dir(&search_dir)? {
-            let entry = entry?;
-            let path = entry.path();
+                // Send first pane commands using precise pane targeting (index 0)
+                let first_pane_command = first_pane.map(PaneConfig::command).unwrap_or("");
+                Self::send_commands(
+                    &mut plan,
+                    session_name,
+                    window_target,
+                    Some(0), // First pane is always index 0 with 0-based indexing
+                    &pre_commands,
+                    first_pane_command,
+                );
 
-            if path.is_file()
-                && path
-                    .extension()
-                    .is_some_and(|ext| ext == "yml" || ext == "yaml")
-            {
-                match Config::parse_file(&path) {
-                    Ok(config) => configs.push(config),
-                    Err(_) => continue, // Skip invalid config files
+                if first_pane
+                    .and_then(PaneConfig::options)
+                    .is_some_and(|options| {
+                        options.split.is_some()
+                            || options.size.is_some()
+                            || options.target.is_some()
+                    })
+                {
+                    return Err(TmuxrsError::InvalidConfig(format!(
+                        "the first pane of window '{window_name}' is not split, so it cannot set split, size or target"
+                    )));
+                }
+
+                // Add additional panes by splitting
+                let splits = Self::pane_positions(window_name, &layout_config.panes)?.splits;
+                for (pane, (split_position, new_position)) in
+                    layout_config.panes.iter().skip(1).zip(splits)
+                {
+                    let pane_root = Self::pane_root(root_path, &window_root, pane);
+                    let options = pane.options();
+                    let target = options.and_then(|options| options.target);
+
+                    // Create split without command to allow proper shell initialization
+                    let direction = options
+                        .and_then(|options| options.split)
+                        .unwrap_or_default();
+                    let size = options
+                        .and_then(|options| options.size)
+                        .map(|size| size.to_string());
+                    plan.push(TmuxCommand::split_pane_command(
+                        session_name,
+                        window_target,
+                        target.map(|_| split_position),
+                        direction == SplitDirection::Vertical,
+                        size.as_deref(),
+                        Some(&pane_root),
+                    ));
+
+                    // Send commands to the new pane using precise pane targeting
+                    Self::send_commands(
+                        &mut plan,
+                        session_name,
+                        window_target,
+                        Some(new_position),
+                        &pre_commands,
+                        pane.command(),
+                    );
+                }
+
+                // Apply layout if specified
+                if let Some(layout) = &layout_config.layout {
+                    plan.push(TmuxCommand::select_layout_command(
+                        session_name,
+                        window_target,
+                        layout,
+                    ));
                 }
             }
         }
 
-        Ok(configs)
+        Ok(plan)
     }
 
-    /// Stop a session
-    pub fn stop_session(&self, name: &str) -> Result<String> {
-        // Check if session exists first
-        if !TmuxCommand::session_exists_with_socket(name, self.socket_path.as_ref())? {
-            return Err(TmuxrsError::TmuxError(format!(
-                "Session '{name}' does not exist"
-            )));
+    /// Replay the splits that create a window's panes, in config order
+    ///
+    /// tmux numbers panes by position and puts a new pane right after the one
+    /// it splits, so a split with a `target` moves the panes after it. Without
+    /// a target a pane splits the previous one, which is the active one.
+    fn pane_positions(window_name: &str, panes: &[PaneConfig]) -> Result<PaneSplits> {
+        let mut splits = Vec::new();
+        let mut positions = vec![0];
+        for (pane_index, pane) in panes.iter().enumerate().skip(1) {
+            let split_from = pane
+                .options()
+                .and_then(|options| options.target)
+                .unwrap_or(pane_index - 1);
+            if split_from >= pane_index {
+                return Err(TmuxrsError::InvalidConfig(format!(
+                    "pane {pane_index} of window '{window_name}' targets pane {split_from}, which is not created before it"
+                )));
+            }
+
+            let split_position = positions[split_from];
+            let new_position = split_position + 1;
+            for position in positions
+                .iter_mut()
+                .filter(|position| **position >= new_position)
+            {
+                *position += 1;
+            }
+            positions.push(new_position);
+            splits.push((split_position, new_position));
         }
+        Ok(PaneSplits { splits, positions })
+    }
 
-        TmuxCommand::kill_session_with_socket(name, self.socket_path.as_ref())?;
-        Ok(format!("Stopped session '{name}'"))
+    /// Add the pre commands followed by the command itself, sent to a window or pane
+    ///
+    /// Blank commands are skipped so that panes without a command just get a shell.
+    fn send_commands(
+        plan: &mut Vec<TmuxCommand>,
+        session_name: &str,
+        window_target: &str,
+        pane_index: Option<usize>,
+        pre_commands: &[&str],
+        command: &str,
+    ) {
+        let commands = pre_commands.iter().copied().chain(std::iter::once(command));
+        for keys in commands.filter(|keys| !keys.trim().is_empty()) {
+            plan.push(match pane_index {
+                Some(pane_index) => TmuxCommand::send_keys_to_pane_command(
+                    session_name,
+                    window_target,
+                    pane_index,
+                    keys,
+                ),
+                None => TmuxCommand::send_keys_command(session_name, window_target, keys),
+            });
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::env;
+    use crate::fake::FakeBackend;
+    use crate::git::test_support::add_worktree;
     use tempfile::TempDir;
 
     #[test]
-    fn test_expand_path_home_directory() {
-        // Test tilde expansion
-        let path = SessionManager::expand_path("~/projects").unwrap();
+    fn test_resolve_root_relative_to_session_root() {
+        let session_root = Path::new("/work/monorepo");
+
+        let path = SessionManager::resolve_root(session_root, "frontend");
+        assert_eq!(path, PathBuf::from("/work/monorepo/frontend"));
+
+        let path = SessionManager::resolve_root(session_root, "/var/log");
+        assert_eq!(path, PathBuf::from("/var/log"));
+
+        let path = SessionManager::resolve_root(session_root, "~/elsewhere");
         assert!(path.is_absolute());
-        assert!(!path.to_string_lossy().contains('~'));
+        assert!(!path.starts_with(session_root));
+    }
+
+    #[test]
+    fn test_pane_root_falls_back_to_window_root() {
+        let session_root = Path::new("/work/monorepo");
+        let window_root = Path::new("/work/monorepo/backend");
+
+        let pane = PaneConfig::Command("cargo run".to_string());
+        let path = SessionManager::pane_root(session_root, window_root, &pane);
+        assert_eq!(path, window_root);
+
+        let pane: PaneConfig = serde_yaml::from_str("root: frontend").unwrap();
+        let path = SessionManager::pane_root(session_root, window_root, &pane);
+        assert_eq!(path, PathBuf::from("/work/monorepo/frontend"));
+    }
+
+    #[test]
+    fn test_startup_target_resolution() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+name: startup
+startup_window: 1
+startup_pane: 2
+windows:
+  - logs: tail -f log/development.log
+  - editor:
+      panes:
+        - vim
+        - cargo watch
+        - git status
+"#,
+        )
+        .unwrap();
+
+        let target = SessionManager::startup_target(&config).unwrap();
+        assert_eq!(target, Some((1, Some(2))));
+    }
+
+    #[test]
+    fn test_startup_pane_follows_split_targets() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+name: startup
+startup_pane: 1
+windows:
+  - main:
+      panes:
+        - vim
+        - cargo watch
+        - command: htop
+          target: 0
+"#,
+        )
+        .unwrap();
+
+        // htop splits pane 0, which moves cargo watch to tmux pane 2
+        let target = SessionManager::startup_target(&config).unwrap();
+        assert_eq!(target, Some((0, Some(2))));
+    }
+
+    #[test]
+    fn test_startup_target_defaults_and_errors() {
+        let parse = |yaml: &str| -> Config { serde_yaml::from_str(yaml).unwrap() };
+
+        let unset = parse("name: s\nwindows:\n  - editor: vim\n");
+        assert_eq!(SessionManager::startup_target(&unset).unwrap(), None);
+
+        let pane_only = parse("name: s\nstartup_pane: 0\nwindows:\n  - editor: vim\n");
+        assert_eq!(
+            SessionManager::startup_target(&pane_only).unwrap(),
+            Some((0, Some(0)))
+        );
+
+        let missing = parse("name: s\nstartup_window: logs\nwindows:\n  - editor: vim\n");
+        assert!(matches!(
+            SessionManager::startup_target(&missing),
+            Err(TmuxrsError::InvalidConfig(_))
+        ));
+
+        let bad_pane = parse("name: s\nstartup_pane: 1\nwindows:\n  - editor: vim\n");
+        assert!(matches!(
+            SessionManager::startup_target(&bad_pane),
+            Err(TmuxrsError::InvalidConfig(_))
+        ));
     }
 
     #[test]
-    fn test_expand_path_environment_variable() {
-        // Set a test environment variable
-        env::set_var("TEST_PATH", "/tmp/test");
+    fn test_plan_session_renders_ordered_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let yaml = r#"
+name: planned
+root: /srv/planned
+pre_window: nvm use
+startup_window: editor
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - command: cargo test
+          root: tests
+  - server: cargo run
+"#;
+        std::fs::write(temp_dir.path().join("planned.yml"), yaml).unwrap();
 
-        let path = SessionManager::expand_path("$TEST_PATH/project").unwrap();
-        assert_eq!(path.to_string_lossy(), "/tmp/test/project");
+        let manager = SessionManager::with_socket("/tmp/plan.sock");
+        let plan = manager
+            .plan_session(Some("planned"), Some(temp_dir.path()), true)
+            .unwrap();
+        let rendered: Vec<String> = plan.iter().map(|cmd| format!("{cmd:#}")).collect();
 
-        // Clean up
-        env::remove_var("TEST_PATH");
+        // The session is built in one invocation, like `start` does
+        assert_eq!(
+            rendered,
+            vec![
+                r"tmux -S /tmp/plan.sock \
+  new-session -d -s planned -c /srv/planned \; \
+  set-option -t planned base-index 0 \; \
+  set-option -t planned pane-base-index 0 \; \
+  move-window -r -t planned \; \
+  rename-window -t planned:0 editor \; \
+  send-keys -t planned:0.0 'nvm use' Enter \; \
+  send-keys -t planned:0.0 vim Enter \; \
+  split-window -h -t planned:0 -c /srv/planned/tests \; \
+  send-keys -t planned:0.1 'nvm use' Enter \; \
+  send-keys -t planned:0.1 'cargo test' Enter \; \
+  select-layout -t planned:0 main-vertical \; \
+  new-window -t planned:1 -n server -c /srv/planned \; \
+  send-keys -t planned:1 'nvm use' Enter \; \
+  send-keys -t planned:1 'cargo run' Enter \; \
+  select-window -t planned:0",
+                "tmux -S /tmp/plan.sock attach-session -t planned",
+            ]
+        );
     }
 
     #[test]
-    fn test_expand_path_no_expansion_needed() {
-        // Test absolute path
-        let path = SessionManager::expand_path("/usr/local/bin").unwrap();
-        assert_eq!(path.to_string_lossy(), "/usr/local/bin");
+    fn test_plan_session_splits_targeted_panes() {
+        let temp_dir = TempDir::new().unwrap();
+        let yaml = r#"
+name: splits
+root: /srv
+windows:
+  - main:
+      panes:
+        - vim
+        - command: cargo watch
+          split: vertical
+          size: 30%
+        - command: htop
+          target: 0
+          size: 20
+"#;
+        std::fs::write(temp_dir.path().join("splits.yml"), yaml).unwrap();
+
+        let plan = SessionManager::new()
+            .plan_session(Some("splits"), Some(temp_dir.path()), false)
+            .unwrap();
+        let rendered = format!("{:#}", plan[0]);
+        let lines: Vec<&str> = rendered.lines().skip(6).collect();
+
+        // htop splits pane 0, so it becomes pane 1 and cargo watch moves to pane 2
+        assert_eq!(
+            lines,
+            vec![
+                r"  send-keys -t splits:0.0 vim Enter \; \",
+                r"  split-window -v -t splits:0 -l 30% -c /srv \; \",
+                r"  send-keys -t splits:0.1 'cargo watch' Enter \; \",
+                r"  split-window -h -t splits:0.0 -l 20 -c /srv \; \",
+                "  send-keys -t splits:0.1 htop Enter",
+            ]
+        );
     }
 
     #[test]
-    fn test_expand_path_combined() {
-        // Test combined tilde and env var
-        env::set_var("TEST_DIR", "mydir");
+    fn test_plan_session_rejects_invalid_split_targets() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |yaml: &str| std::fs::write(temp_dir.path().join("bad.yml"), yaml).unwrap();
+        let plan = || SessionManager::new().plan_session(Some("bad"), Some(temp_dir.path()), false);
 
-        let path = SessionManager::expand_path("~/$TEST_DIR/project").unwrap();
-        assert!(path.is_absolute());
-        assert!(path.to_string_lossy().contains("mydir/project"));
-        assert!(!path.to_string_lossy().contains('~'));
-        assert!(!path.to_string_lossy().contains("$TEST_DIR"));
+        write("name: bad\nwindows:\n  - main:\n      panes:\n        - vim\n        - target: 1\n");
+        assert!(matches!(plan(), Err(TmuxrsError::InvalidConfig(_))));
+
+        write("name: bad\nwindows:\n  - main:\n      panes:\n        - split: vertical\n");
+        assert!(matches!(plan(), Err(TmuxrsError::InvalidConfig(_))));
+    }
 
-        // Clean up
-        env::remove_var("TEST_DIR");
+    #[test]
+    fn test_dry_run_records_start_and_stop() {
+        let temp_dir = TempDir::new().unwrap();
+        let yaml = r#"
+name: rehearsed
+root: /srv/rehearsed
+on_project_first_start: touch first-start
+on_project_stop: touch stopped
+windows:
+  - editor: vim
+"#;
+        std::fs::write(temp_dir.path().join("rehearsed.yml"), yaml).unwrap();
+
+        let manager = SessionManager::with_socket("/tmp/dry.sock").dry_run(false);
+        manager
+            .start_session_with_options(Some("rehearsed"), Some(temp_dir.path()), false, false)
+            .unwrap();
+        let steps = manager.backend().steps();
+
+        assert_eq!(
+            crate::plan::render_text(&steps),
+            r"tmux -S /tmp/dry.sock has-session -t =rehearsed
+(cd /srv/rehearsed && touch first-start)  # on_project_first_start
+tmux -S /tmp/dry.sock \
+  new-session -d -s rehearsed -c /srv/rehearsed \; \
+  set-option -t rehearsed base-index 0 \; \
+  set-option -t rehearsed pane-base-index 0 \; \
+  move-window -r -t rehearsed \; \
+  rename-window -t rehearsed:0 editor \; \
+  send-keys -t rehearsed:0 vim Enter
+"
+        );
+
+        let manager = SessionManager::new().dry_run(true);
+        manager
+            .stop_session_with_options("rehearsed", Some(temp_dir.path()))
+            .unwrap();
+        let steps = manager.backend().steps();
+
+        assert_eq!(
+            crate::plan::render_text(&steps),
+            "tmux has-session -t =rehearsed\n\
+             tmux kill-session -t rehearsed\n\
+             (cd /srv/rehearsed && touch stopped)  # on_project_stop\n"
+        );
+    }
+
+    #[test]
+    fn test_append_dry_run_asks_the_server_for_windows() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("proj.yml");
+        std::fs::write(
+            &config_file,
+            "name: proj\nroot: /srv/proj\nwindows:\n  - editor: vim\n  - shell: ''\n",
+        )
+        .unwrap();
+        let manager = SessionManager::with_backend(FakeBackend::new());
+        manager
+            .start_session_with_options(Some("proj"), Some(temp_dir.path()), false, false)
+            .unwrap();
+
+        // Windows 0 and 1 exist, so only `logs` is added, after them
+        std::fs::write(
+            &config_file,
+            "name: proj\nroot: /srv/proj\nwindows:\n  - editor: vim\n  - logs: tail -f log\n",
+        )
+        .unwrap();
+        let manager = manager.dry_run_on_server();
+        let summary = manager
+            .start_session_with_options(Some("proj"), Some(temp_dir.path()), false, true)
+            .unwrap();
+        assert_eq!(
+            summary,
+            "Appended 1 window(s) to session 'proj' (added: logs); skipped existing window(s): editor"
+        );
+        let steps = manager.backend().steps();
+        assert_eq!(
+            crate::plan::render_text(&steps[2..]),
+            "tmux \\\n  \
+             new-window -t proj:2 -n logs -c /srv/proj \\; \\\n  \
+             send-keys -t proj:2 'tail -f log' Enter\n"
+        );
+
+        // Without a server to ask, a real run would create the session
+        let manager =
+            SessionManager::with_socket(temp_dir.path().join("no-server.sock")).dry_run_on_server();
+        manager
+            .start_session_with_options(Some("proj"), Some(temp_dir.path()), false, true)
+            .unwrap();
+        let steps = manager.backend().steps();
+        assert!(
+            crate::plan::render_text(&steps).contains("new-session -d -s proj"),
+            "{steps:?}"
+        );
     }
 
     #[test]
@@ -431,13 +1971,237 @@ windows:
         let configs = manager.list_configs(Some(temp_dir.path())).unwrap();
         assert_eq!(configs.len(), 2);
 
-        let names: Vec<String> = configs.iter().map(|c| c.name.clone()).collect();
+        let names: Vec<String> = configs
+            .iter()
+            .map(|c| c.config.as_ref().unwrap().name.clone())
+            .collect();
         assert!(names.contains(&"project1".to_string()));
         assert!(names.contains(&"project2".to_string()));
     }
 
     #[test]
-    fn test_list_configs_skips_invalid_yaml() {
+    fn test_config_for_directory_prefers_deepest_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let work = std::fs::canonicalize(temp_dir.path()).unwrap();
+        let web = work.join("acme").join("web");
+        std::fs::create_dir_all(web.join("src")).unwrap();
+
+        let config_dir = temp_dir.path().join("configs");
+        std::fs::create_dir(&config_dir).unwrap();
+        let write = |name: &str, root: Option<&Path>| {
+            let root = root.map_or_else(String::new, |root| format!("root: {}\n", root.display()));
+            std::fs::write(
+                config_dir.join(format!("{name}.yml")),
+                format!("name: {name}\n{root}windows:\n  - shell: ''\n"),
+            )
+            .unwrap();
+        };
+        write("acme", Some(&work.join("acme")));
+        write("acme-web", Some(&web));
+        write("scratch", None);
+        write("everything", Some(Path::new("/")));
+
+        let manager = SessionManager::new();
+        let files = manager.list_configs(Some(&config_dir)).unwrap();
+        let detect = |dir: &Path| SessionManager::config_for_directory(&files, dir).unwrap();
+        assert_eq!(detect(&web.join("src")).as_deref(), Some("acme-web"));
+        assert_eq!(detect(&work.join("acme")).as_deref(), Some("acme"));
+        assert_eq!(detect(&work), None);
+
+        // A second config rooted at the same directory makes it ambiguous
+        write("acme-web-ops", Some(&web));
+        let files = manager.list_configs(Some(&config_dir)).unwrap();
+        match SessionManager::config_for_directory(&files, &web.join("src")) {
+            Err(TmuxrsError::AmbiguousConfig { names, .. }) => {
+                assert_eq!(names, vec!["acme-web", "acme-web-ops"]);
+            }
+            other => panic!("Expected AmbiguousConfig error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_worktree_session_names() {
+        assert_eq!(
+            SessionManager::worktree_session_name("acme", Path::new("/src/acme-v1.2:rc")),
+            "acme@acme-v1-2-rc"
+        );
+        assert_eq!(
+            SessionManager::split_worktree_session_name("acme@feature-x"),
+            Some(("acme", "feature-x"))
+        );
+        assert_eq!(
+            SessionManager::split_worktree_session_name("me@work@feature-x"),
+            Some(("me@work", "feature-x"))
+        );
+        assert_eq!(
+            SessionManager::worktree_session_name("me@work", Path::new("/src/fix@v2")),
+            "me@work@fix-v2"
+        );
+        assert_eq!(SessionManager::split_worktree_session_name("acme"), None);
+        assert_eq!(
+            SessionManager::split_worktree_session_name("@feature-x"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_worktree_session_uses_config_rebased_to_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = std::fs::canonicalize(temp_dir.path()).unwrap();
+        let main = base.join("acme");
+        let worktree = base.join("feature.x");
+        std::fs::create_dir_all(main.join("web")).unwrap();
+        add_worktree(&main.join(".git"), &worktree).unwrap();
+        std::fs::create_dir_all(worktree.join("web").join("src")).unwrap();
+
+        let config_dir = base.join("configs");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("acme.yml"),
+            format!(
+                "name: acme\nroot: {}\nwindows:\n  - server: cargo run\n",
+                main.join("web").display()
+            ),
+        )
+        .unwrap();
+
+        let manager = SessionManager::new();
+        assert_eq!(
+            manager
+                .detect_session_name(&worktree.join("web").join("src"), Some(&config_dir))
+                .unwrap(),
+            "acme@feature-x"
+        );
+
+        let plan = manager
+            .plan_session(Some("acme@feature-x"), Some(&config_dir), false)
+            .unwrap();
+        let root = worktree.join("web").display().to_string();
+        assert_eq!(
+            plan[0].commands()[0],
+            vec!["new-session", "-d", "-s", "acme@feature-x", "-c", &root]
+        );
+
+        match manager.plan_session(Some("acme@other"), Some(&config_dir), false) {
+            Err(TmuxrsError::WorktreeNotFound { name, worktree }) => {
+                assert_eq!((name.as_str(), worktree.as_str()), ("acme", "other"));
+            }
+            other => panic!("Expected WorktreeNotFound error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_worktree_sessions_with_fake_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = std::fs::canonicalize(temp_dir.path()).unwrap();
+        let main = base.join("acme");
+        std::fs::create_dir_all(main.join(".git")).unwrap();
+        for worktree in ["bugfix", "feature-x"] {
+            add_worktree(&main.join(".git"), &base.join(worktree)).unwrap();
+        }
+        let config_dir = base.join("configs");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("acme.yml"),
+            format!(
+                "name: acme\nroot: {}\nwindows:\n  - shell: ''\n",
+                main.display()
+            ),
+        )
+        .unwrap();
+
+        let manager = SessionManager::with_backend(FakeBackend::new());
+        manager
+            .start_session_with_options(Some("acme@feature-x"), Some(&config_dir), false, false)
+            .unwrap();
+        assert_eq!(
+            manager
+                .backend()
+                .session("acme@feature-x")
+                .unwrap()
+                .working_dir,
+            base.join("feature-x")
+        );
+
+        assert_eq!(
+            manager
+                .worktree_sessions("acme", Some(&config_dir))
+                .unwrap(),
+            vec![
+                WorktreeSession {
+                    session: "acme".to_string(),
+                    path: main,
+                    running: false,
+                },
+                WorktreeSession {
+                    session: "acme@bugfix".to_string(),
+                    path: base.join("bugfix"),
+                    running: false,
+                },
+                WorktreeSession {
+                    session: "acme@feature-x".to_string(),
+                    path: base.join("feature-x"),
+                    running: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktrees_sharing_a_session_name_are_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = std::fs::canonicalize(temp_dir.path()).unwrap();
+        let main = base.join("acme");
+        std::fs::create_dir_all(main.join(".git")).unwrap();
+        for worktree in ["feature-x", "feature.x", "bugfix"] {
+            add_worktree(&main.join(".git"), &base.join(worktree)).unwrap();
+        }
+        let config_dir = base.join("configs");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("acme.yml"),
+            format!(
+                "name: acme\nroot: {}\nwindows:\n  - shell: ''\n",
+                main.display()
+            ),
+        )
+        .unwrap();
+
+        let expected_paths = vec![
+            base.join("feature-x").display().to_string(),
+            base.join("feature.x").display().to_string(),
+        ];
+        let manager = SessionManager::with_backend(FakeBackend::new());
+        match manager.start_session_with_options(
+            Some("acme@feature-x"),
+            Some(&config_dir),
+            false,
+            false,
+        ) {
+            Err(TmuxrsError::AmbiguousWorktree { session, paths }) => {
+                assert_eq!(session, "acme@feature-x");
+                assert_eq!(paths, expected_paths);
+            }
+            other => panic!("Expected AmbiguousWorktree error, got {other:?}"),
+        }
+        assert!(manager.backend().sessions().is_empty());
+
+        // Other worktrees are unaffected
+        manager
+            .start_session_with_options(Some("acme@bugfix"), Some(&config_dir), false, false)
+            .unwrap();
+
+        match manager.worktree_sessions("acme", Some(&config_dir)) {
+            Err(TmuxrsError::AmbiguousWorktree { session, paths }) => {
+                assert_eq!(session, "acme@feature-x");
+                assert_eq!(paths, expected_paths);
+            }
+            other => panic!("Expected AmbiguousWorktree error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_list_configs_reports_invalid_yaml() {
         let temp_dir = TempDir::new().unwrap();
         let manager = SessionManager::new();
 
@@ -458,8 +2222,68 @@ windows:
         .unwrap();
 
         let configs = manager.list_configs(Some(temp_dir.path())).unwrap();
-        assert_eq!(configs.len(), 1);
-        assert_eq!(configs[0].name, "valid");
+        assert_eq!(configs.len(), 2);
+
+        // Files are listed in path order, so the invalid one comes first
+        let error = configs[0].config.as_ref().unwrap_err();
+        assert_eq!(error.path, temp_dir.path().join("invalid.yml"));
+        assert_eq!((error.line, error.column), (Some(1), Some(1)));
+        assert_eq!(configs[1].config.as_ref().unwrap().name, "valid");
+    }
+
+    #[test]
+    fn test_format_append_summary() {
+        let added = vec!["logs".to_string(), "db".to_string()];
+        let skipped = vec!["editor".to_string()];
+
+        let summary = SessionManager::format_append_summary("proj", &added, &skipped);
+        assert_eq!(
+            summary,
+            "Appended 2 window(s) to session 'proj' (added: logs, db); skipped existing window(s): editor"
+        );
+
+        let summary = SessionManager::format_append_summary("proj", &[], &skipped);
+        assert_eq!(
+            summary,
+            "Appended 0 window(s) to session 'proj'; skipped existing window(s): editor"
+        );
+    }
+
+    #[test]
+    fn test_run_hook_runs_commands_in_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let hook = Hook::Multiple(vec![
+            "echo first > hook.log".to_string(),
+            "echo second >> hook.log".to_string(),
+        ]);
+
+        SessionManager::new()
+            .run_hook("on_project_start", Some(&hook), temp_dir.path())
+            .unwrap();
+
+        let log = std::fs::read_to_string(temp_dir.path().join("hook.log")).unwrap();
+        assert_eq!(log, "first\nsecond\n");
+    }
+
+    #[test]
+    fn test_run_hook_reports_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let hook = Hook::Multiple(vec![
+            "exit 7".to_string(),
+            "touch should-not-run".to_string(),
+        ]);
+
+        let result =
+            SessionManager::new().run_hook("on_project_stop", Some(&hook), temp_dir.path());
+
+        match result {
+            Err(TmuxrsError::HookFailed { hook, status }) => {
+                assert_eq!(hook, "on_project_stop");
+                assert_eq!(status.code(), Some(7));
+            }
+            other => panic!("Expected HookFailed error, got {other:?}"),
+        }
+        assert!(!temp_dir.path().join("should-not-run").exists());
     }
 
     #[test]
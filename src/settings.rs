@@ -0,0 +1,86 @@
+use crate::config::{expand_path, ConfigParseError};
+use crate::error::Result;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the settings file inside the tmuxrs config directory
+///
+/// It is a dotfile so that it is never mistaken for a session config.
+pub const SETTINGS_FILE: &str = ".settings.yml";
+
+/// User settings for tmuxrs itself, as opposed to session configs
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Extra directories to search for configs, after the built-in ones
+    #[serde(default)]
+    pub config_dirs: Vec<String>,
+}
+
+impl Settings {
+    /// The settings file inside a tmuxrs config directory
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join(SETTINGS_FILE)
+    }
+
+    /// Read the settings file of a config directory; a missing file means default settings
+    pub fn load(config_dir: &Path) -> Result<Settings> {
+        let path = Self::path(config_dir);
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let settings = serde_yaml::from_str(&content)
+            .map_err(|error| ConfigParseError::new(&path, &content, &error))?;
+        Ok(settings)
+    }
+
+    /// `config_dirs` with `~` and environment variables expanded
+    pub fn extra_config_dirs(&self) -> Vec<PathBuf> {
+        self.config_dirs
+            .iter()
+            .map(|dir| expand_path(dir))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TmuxrsError;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(Settings::load(temp_dir.path())
+            .unwrap()
+            .config_dirs
+            .is_empty());
+
+        std::fs::write(
+            Settings::path(temp_dir.path()),
+            "config_dirs:\n  - /srv/shared/tmuxrs\n  - ~/work/tmux\n",
+        )
+        .unwrap();
+        let settings = Settings::load(temp_dir.path()).unwrap();
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            settings.extra_config_dirs(),
+            vec![
+                PathBuf::from("/srv/shared/tmuxrs"),
+                home.join("work").join("tmux")
+            ]
+        );
+
+        std::fs::write(Settings::path(temp_dir.path()), "config_dir: /typo\n").unwrap();
+        match Settings::load(temp_dir.path()) {
+            Err(TmuxrsError::ConfigParse(err)) => {
+                assert_eq!(err.line, Some(1));
+                assert!(err.message.contains("unknown field `config_dir`"));
+            }
+            other => panic!("Expected ConfigParse error, got {other:?}"),
+        }
+    }
+}
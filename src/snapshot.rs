@@ -0,0 +1,657 @@
+use crate::config::{Config, PaneConfig, PaneOptions, WindowConfig, WindowLayout};
+use crate::error::{Result, TmuxrsError};
+use crate::tmux::TmuxCommand;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// `list-sessions` format: name and working directory
+pub const SESSION_FORMAT: &str = "#{session_name}\t#{session_path}";
+
+/// `list-windows` format: index, name and layout
+pub const WINDOW_FORMAT: &str = "#{window_index}\t#{window_name}\t#{window_layout}";
+
+/// `list-panes` format: window index, pane index, working directory, process
+/// ID and foreground program
+pub const PANE_FORMAT: &str =
+    "#{window_index}\t#{pane_index}\t#{pane_current_path}\t#{pane_pid}\t#{pane_current_command}";
+
+/// Programs that `#{pane_current_command}` reports for a pane sitting at a prompt
+const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "tcsh", "csh", "nu",
+];
+
+/// The state of a running session, as reported by tmux
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub name: String,
+    /// The session's working directory (`#{session_path}`)
+    pub root: PathBuf,
+    /// Windows in index order
+    pub windows: Vec<WindowSnapshot>,
+}
+
+/// A window of a `SessionSnapshot`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub index: usize,
+    pub name: String,
+    /// The custom layout string from `#{window_layout}`
+    pub layout: Option<String>,
+    /// Panes in index order
+    pub panes: Vec<PaneSnapshot>,
+}
+
+/// A pane of a `WindowSnapshot`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaneSnapshot {
+    pub index: usize,
+    pub working_dir: PathBuf,
+    /// The foreground program, or `None` when the pane is at a shell prompt
+    pub command: Option<String>,
+    /// The program's full command line, when it could be read from the process list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    /// The pane's history and visible contents, when captured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollback: Option<String>,
+    /// The pane's process (`#{pane_pid}`), only meaningful while the session runs
+    #[serde(skip)]
+    pub pid: Option<u32>,
+}
+
+impl SessionSnapshot {
+    /// Parse `list-sessions` output in `SESSION_FORMAT` into names and working directories
+    pub fn parse_sessions(output: &str) -> Vec<(String, PathBuf)> {
+        output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(name, path)| (name.to_string(), PathBuf::from(path)))
+            .collect()
+    }
+
+    /// Parse `list-windows` output in `WINDOW_FORMAT`; panes are added by `add_panes`
+    pub fn parse_windows(output: &str) -> Result<Vec<WindowSnapshot>> {
+        output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let [index, name, layout] = split_fields(line, "list-windows")?;
+                Ok(WindowSnapshot {
+                    index: parse_index(index, line, "list-windows")?,
+                    name: name.to_string(),
+                    layout: (!layout.is_empty()).then(|| layout.to_string()),
+                    panes: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Add the panes from `list-panes` output in `PANE_FORMAT` to their windows
+    pub fn add_panes(windows: &mut [WindowSnapshot], output: &str) -> Result<()> {
+        for line in output.lines().filter(|line| !line.is_empty()) {
+            let [window_index, pane_index, working_dir, pid, command] =
+                split_fields(line, "list-panes")?;
+            let window_index = parse_index(window_index, line, "list-panes")?;
+            let window = windows
+                .iter_mut()
+                .find(|window| window.index == window_index)
+                .ok_or_else(|| {
+                    TmuxrsError::TmuxError(format!("list-panes: no window {window_index}"))
+                })?;
+
+            let command = command.trim_start_matches('-');
+            window.panes.push(PaneSnapshot {
+                index: parse_index(pane_index, line, "list-panes")?,
+                working_dir: PathBuf::from(working_dir),
+                command: (!command.is_empty() && !SHELLS.contains(&command))
+                    .then(|| command.to_string()),
+                command_line: None,
+                scrollback: None,
+                pid: pid.parse().ok(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Describe the session as a config that recreates its windows, layouts
+    /// and directories, and with `with_commands`, the programs of its panes
+    ///
+    /// Each program runs its command line, or only its name when the command
+    /// line could not be read. Directories are written relative to the session
+    /// root where possible, and the root itself relative to the home directory.
+    pub fn to_config(&self, with_commands: bool) -> Config {
+        self.config(|_, pane| {
+            pane.command_line
+                .clone()
+                .or_else(|| pane.command.clone())
+                .filter(|_| with_commands)
+        })
+    }
+
+    /// The config that `tmuxrs restore` builds the session from: `to_config`,
+    /// with every pane that has captured scrollback printing it, and with
+    /// `start_commands`, running the command line of its program
+    ///
+    /// Programs whose command line could not be read are not started, since
+    /// running `cargo` for a pane that was running `cargo test --watch` would
+    /// do the wrong thing; `unstarted_programs` lists them.
+    /// Each pane's scrollback is written to `dir/<session>/<window>.<pane>.txt`.
+    pub fn to_restore_config(&self, dir: &Path, start_commands: bool) -> Result<Config> {
+        let session_dir = dir.join(self.name.replace('/', "_"));
+        let mut scrollback_files = HashMap::new();
+        for window in &self.windows {
+            for pane in &window.panes {
+                let Some(scrollback) = &pane.scrollback else {
+                    continue;
+                };
+                std::fs::create_dir_all(&session_dir)?;
+                let file = session_dir.join(format!("{}.{}.txt", window.index, pane.index));
+                std::fs::write(&file, scrollback)?;
+                scrollback_files.insert((window.index, pane.index), file);
+            }
+        }
+
+        Ok(self.config(|window, pane| {
+            let print_scrollback = scrollback_files
+                .get(&(window.index, pane.index))
+                .map(|file| {
+                    format!(
+                        "clear; cat -- {}",
+                        TmuxCommand::quote(&file.to_string_lossy())
+                    )
+                });
+            let program = pane.command_line.as_ref().filter(|_| start_commands);
+            match (print_scrollback, program) {
+                (Some(print_scrollback), Some(program)) => {
+                    Some(format!("{print_scrollback}; {program}"))
+                }
+                (print_scrollback, None) => print_scrollback,
+                (None, program) => program.cloned(),
+            }
+        }))
+    }
+
+    /// The programs that were running, as `<window>, pane <index>: <program>`
+    ///
+    /// Windows are named as in `to_config`, and programs are given by their
+    /// command line when it is known.
+    pub fn programs(&self) -> Vec<String> {
+        self.programs_where(|_| true)
+    }
+
+    /// The `programs` that `to_restore_config` does not start, because their
+    /// command line could not be read or `start_commands` is not set
+    pub fn unstarted_programs(&self, start_commands: bool) -> Vec<String> {
+        self.programs_where(|pane| !start_commands || pane.command_line.is_none())
+    }
+
+    fn programs_where(&self, include: impl Fn(&PaneSnapshot) -> bool) -> Vec<String> {
+        self.window_names()
+            .iter()
+            .zip(&self.windows)
+            .flat_map(|(name, window)| {
+                window
+                    .panes
+                    .iter()
+                    .filter(|pane| include(pane))
+                    .filter_map(move |pane| {
+                        let program = pane.command_line.as_ref().or(pane.command.as_ref())?;
+                        Some(format!("{name}, pane {}: {program}", pane.index))
+                    })
+            })
+            .collect()
+    }
+
+    /// The config file that `tmuxrs freeze` writes: `to_config` as YAML,
+    /// preceded by a comment listing the programs that were running
+    ///
+    /// With `with_commands`, the comment warns that the commands are only as
+    /// good as the process list they were read from.
+    pub fn to_frozen_yaml(&self, with_commands: bool) -> Result<String> {
+        let mut yaml = String::new();
+        let programs = self.programs();
+        if !programs.is_empty() {
+            yaml.push_str(if with_commands {
+                "# Programs that were running when the session was frozen. Their arguments\n\
+                 # were read from the process list, which drops any quoting, and a program\n\
+                 # whose arguments could not be read runs by name alone; check the pane\n\
+                 # commands below, or freeze with --no-commands to leave them out.\n"
+            } else {
+                "# Programs that were running when the session was frozen. They are not\n\
+                 # started; add them to their panes to run them again.\n"
+            });
+            for program in programs {
+                yaml.push_str(&format!("#   {program}\n"));
+            }
+        }
+        yaml.push_str(&serde_yaml::to_string(&self.to_config(with_commands))?);
+        Ok(yaml)
+    }
+
+    /// The config for the session, with each pane running what `command`
+    /// returns for it
+    fn config(&self, command: impl Fn(&WindowSnapshot, &PaneSnapshot) -> Option<String>) -> Config {
+        let windows = self
+            .window_names()
+            .into_iter()
+            .zip(&self.windows)
+            .map(|(name, window)| self.window_config(name, window, &command))
+            .collect();
+
+        Config {
+            name: self.name.clone(),
+            root: Some(Config::root_value(&self.root)),
+            pre_window: None,
+            startup_window: None,
+            startup_pane: None,
+            on_project_start: None,
+            on_project_first_start: None,
+            on_project_restart: None,
+            on_project_exit: None,
+            on_project_stop: None,
+            windows,
+        }
+    }
+
+    /// Window names made unique, since tmux cannot target a window by a name
+    /// that several windows share
+    ///
+    /// Later windows with a taken name get a `-2`, `-3`, ... suffix.
+    fn window_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for window in &self.windows {
+            let mut name = window.name.clone();
+            let mut suffix = 2;
+            while names.contains(&name)
+                || (name != window.name && self.windows.iter().any(|other| other.name == name))
+            {
+                name = format!("{}-{suffix}", window.name);
+                suffix += 1;
+            }
+            names.push(name);
+        }
+        names
+    }
+
+    fn window_config(
+        &self,
+        name: String,
+        window: &WindowSnapshot,
+        command: &impl Fn(&WindowSnapshot, &PaneSnapshot) -> Option<String>,
+    ) -> WindowConfig {
+        let command = |pane: &PaneSnapshot| command(window, pane);
+
+        // The first pane's directory is the one the window was created in
+        let window_dir = window
+            .panes
+            .first()
+            .map_or(self.root.as_path(), |pane| pane.working_dir.as_path());
+
+        if let [pane] = window.panes.as_slice() {
+            if window_dir == self.root {
+                return WindowConfig::Complex {
+                    window: IndexMap::from([(name, command(pane).unwrap_or_default())]),
+                };
+            }
+        }
+
+        let panes = window
+            .panes
+            .iter()
+            .map(|pane| {
+                if pane.working_dir == window_dir {
+                    return PaneConfig::Command(command(pane).unwrap_or_default());
+                }
+                PaneConfig::Detailed(PaneOptions {
+                    command: command(pane),
+                    root: relative_root(&pane.working_dir, &self.root),
+                    split: None,
+                    size: None,
+                    target: None,
+                })
+            })
+            .collect();
+
+        let layout = WindowLayout {
+            layout: window.layout.clone().filter(|_| window.panes.len() > 1),
+            root: relative_root(window_dir, &self.root),
+            pre: None,
+            panes,
+        };
+        WindowConfig::WithLayout {
+            window: IndexMap::from([(name, layout)]),
+        }
+    }
+}
+
+/// Find the command line of `program` in `ps -o pid= -o ppid= -o args=` output
+///
+/// `#{pane_current_command}` only names the pane's foreground program, so the
+/// pane's process and its descendants are searched, nearest first, for one
+/// started as `program`. Arguments are joined with spaces by `ps`, so any that
+/// contained spaces lose their quoting.
+pub fn find_command_line(processes: &str, pane_pid: u32, program: &str) -> Option<String> {
+    let processes: Vec<(u32, u32, &str)> = processes
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = line.trim_start().split_once(char::is_whitespace)?;
+            let (ppid, args) = rest.trim_start().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, ppid.parse().ok()?, args.trim()))
+        })
+        .collect();
+
+    let mut queue = VecDeque::from([pane_pid]);
+    let mut seen = HashSet::new();
+    while let Some(pid) = queue.pop_front() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        for (process, parent, args) in &processes {
+            if *process == pid {
+                let name = args.split_whitespace().next().unwrap_or_default();
+                let name = name.rsplit('/').next().unwrap_or(name);
+                if name.trim_start_matches('-') == program {
+                    return Some(args.to_string());
+                }
+            }
+            if *parent == pid {
+                queue.push_back(*process);
+            }
+        }
+    }
+    None
+}
+
+/// Split a line of format output into exactly `N` tab-separated fields
+///
+/// The last field keeps any further tabs, since it may contain them.
+fn split_fields<'a, const N: usize>(line: &'a str, command: &str) -> Result<[&'a str; N]> {
+    let mut fields = line.splitn(N, '\t');
+    let mut result = [""; N];
+    for field in result.iter_mut() {
+        *field = fields.next().ok_or_else(|| {
+            TmuxrsError::TmuxError(format!("{command}: unexpected output '{line}'"))
+        })?;
+    }
+    Ok(result)
+}
+
+fn parse_index(field: &str, line: &str, command: &str) -> Result<usize> {
+    field
+        .parse()
+        .map_err(|_| TmuxrsError::TmuxError(format!("{command}: unexpected output '{line}'")))
+}
+
+/// A directory for a config `root` field, relative to `base` when it is inside it
+///
+/// Returns `None` when the directory is `base` itself.
+fn relative_root(dir: &Path, base: &Path) -> Option<String> {
+    match dir.strip_prefix(base) {
+        Ok(relative) if relative.as_os_str().is_empty() => None,
+        Ok(relative) => Some(relative.display().to_string()),
+        Err(_) => Some(Config::root_value(dir)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = "6d71,160x48,0,0{80x48,0,0,0,79x48,81,0[79x23,81,0,1,79x24,81,24,2]}";
+
+    fn snapshot() -> SessionSnapshot {
+        let mut windows = SessionSnapshot::parse_windows(&format!(
+            "1\teditor\t{LAYOUT}\n2\tserver\tcc00,160x48,0,0,3\n3\tlogs\tcc00,160x48,0,0,4\n"
+        ))
+        .unwrap();
+        SessionSnapshot::add_panes(
+            &mut windows,
+            "1\t0\t/srv/app\t101\tnvim\n1\t1\t/srv/app\t102\t-zsh\n\
+             1\t2\t/srv/app/tests\t103\tcargo\n\
+             2\t0\t/srv/app\t201\tcargo\n\
+             3\t0\t/var/log\t301\ttail\n",
+        )
+        .unwrap();
+
+        SessionSnapshot {
+            name: "app".to_string(),
+            root: PathBuf::from("/srv/app"),
+            windows,
+        }
+    }
+
+    #[test]
+    fn test_parse_format_output() {
+        let snapshot = snapshot();
+        assert_eq!(snapshot.windows.len(), 3);
+
+        let editor = &snapshot.windows[0];
+        assert_eq!((editor.index, editor.name.as_str()), (1, "editor"));
+        assert_eq!(editor.layout.as_deref(), Some(LAYOUT));
+        assert_eq!(editor.panes[0].command.as_deref(), Some("nvim"));
+        assert_eq!(editor.panes[1].command, None, "shells are not commands");
+        assert_eq!(editor.panes[2].working_dir, PathBuf::from("/srv/app/tests"));
+        assert_eq!(editor.panes[2].pid, Some(103));
+
+        assert_eq!(
+            SessionSnapshot::parse_sessions("web\t/srv/web\napp\t/srv/app\n"),
+            vec![
+                ("web".to_string(), PathBuf::from("/srv/web")),
+                ("app".to_string(), PathBuf::from("/srv/app")),
+            ]
+        );
+        assert!(SessionSnapshot::parse_windows("not a window").is_err());
+        assert!(SessionSnapshot::add_panes(&mut [], "9\t0\t/tmp\t1\tsh").is_err());
+    }
+
+    #[test]
+    fn test_to_config_round_trips_through_yaml() {
+        let config = snapshot().to_config(true);
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        let parsed: Config = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(parsed.name, "app");
+        assert_eq!(parsed.root.as_deref(), Some("/srv/app"));
+        assert!(!yaml.contains("null"), "unset fields are omitted:\n{yaml}");
+
+        match &parsed.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                let editor = &window["editor"];
+                assert_eq!(editor.layout.as_deref(), Some(LAYOUT));
+                assert_eq!(editor.root, None);
+                assert_eq!(editor.panes[0].command(), "nvim");
+                assert_eq!(editor.panes[1].command(), "");
+                assert_eq!(editor.panes[2].command(), "cargo");
+                assert_eq!(editor.panes[2].root(), Some("tests"));
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+
+        match &parsed.windows[1] {
+            WindowConfig::Complex { window } => assert_eq!(window["server"], "cargo"),
+            other => panic!("Expected Complex window, got {other:?}"),
+        }
+
+        match &parsed.windows[2] {
+            WindowConfig::WithLayout { window } => {
+                let logs = &window["logs"];
+                assert_eq!(logs.layout, None, "single panes need no layout");
+                assert_eq!(logs.root.as_deref(), Some("/var/log"));
+                assert_eq!(logs.panes[0].command(), "tail");
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_frozen_config_writes_or_lists_programs() {
+        let mut snapshot = snapshot();
+        snapshot.windows[0].panes[0].command_line = Some("nvim src/main.rs".to_string());
+
+        let yaml = snapshot.to_frozen_yaml(true).unwrap();
+        assert!(
+            yaml.contains("# commands below, or freeze with --no-commands"),
+            "{yaml}"
+        );
+        assert!(
+            yaml.contains("\n#   editor, pane 0: nvim src/main.rs\n"),
+            "{yaml}"
+        );
+        let parsed: Config = serde_yaml::from_str(&yaml).unwrap();
+        match &parsed.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                let panes = &window["editor"].panes;
+                assert_eq!(panes[0].command(), "nvim src/main.rs");
+                assert_eq!(
+                    panes[2].command(),
+                    "cargo",
+                    "only the name of cargo is known"
+                );
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+
+        let yaml = snapshot.to_frozen_yaml(false).unwrap();
+        assert!(
+            yaml.starts_with("# Programs that were running when the session was frozen."),
+            "{yaml}"
+        );
+        for program in [
+            "editor, pane 0: nvim src/main.rs",
+            "editor, pane 2: cargo",
+            "logs, pane 0: tail",
+        ] {
+            assert!(yaml.contains(&format!("\n#   {program}\n")), "{yaml}");
+        }
+
+        let parsed: Config = serde_yaml::from_str(&yaml).unwrap();
+        match &parsed.windows[0] {
+            WindowConfig::WithLayout { window } => {
+                assert!(window["editor"]
+                    .panes
+                    .iter()
+                    .all(|pane| pane.command().is_empty()));
+            }
+            other => panic!("Expected WithLayout window, got {other:?}"),
+        }
+        match &parsed.windows[1] {
+            WindowConfig::Complex { window } => assert_eq!(window["server"], ""),
+            other => panic!("Expected Complex window, got {other:?}"),
+        }
+
+        let mut idle = snapshot;
+        for window in &mut idle.windows {
+            for pane in &mut window.panes {
+                pane.command = None;
+                pane.command_line = None;
+            }
+        }
+        assert!(idle
+            .to_frozen_yaml(true)
+            .unwrap()
+            .starts_with("name: app\n"));
+    }
+
+    #[test]
+    fn test_find_command_line_in_process_list() {
+        let processes = "    1     0 /sbin/init\n\
+                         \x20 101     1 -zsh\n\
+                         \x20 102   101 cargo watch -x test\n\
+                         \x20 103   102 /usr/bin/cargo test\n\
+                         \x20 201     1 /usr/local/bin/nvim src/main.rs\n";
+
+        // The nearest process started as the program is the one the shell ran
+        assert_eq!(
+            find_command_line(processes, 101, "cargo").as_deref(),
+            Some("cargo watch -x test")
+        );
+        // A pane can run the program itself, with a full path
+        assert_eq!(
+            find_command_line(processes, 201, "nvim").as_deref(),
+            Some("/usr/local/bin/nvim src/main.rs")
+        );
+        assert_eq!(find_command_line(processes, 101, "vim"), None);
+        assert_eq!(find_command_line(processes, 999, "cargo"), None);
+    }
+
+    #[test]
+    fn test_restore_config_starts_known_command_lines() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut snapshot = snapshot();
+        snapshot.windows[0].panes[0].command_line = Some("nvim src/main.rs".to_string());
+        snapshot.windows[0].panes[0].scrollback = Some("$ nvim\n".to_string());
+        snapshot.windows[1].panes[0].command_line = Some("cargo run".to_string());
+
+        let scrollback = temp_dir.path().join("app").join("1.0.txt");
+        let print_scrollback = format!(
+            "clear; cat -- {}",
+            TmuxCommand::quote(&scrollback.to_string_lossy())
+        );
+        let config = snapshot.to_restore_config(temp_dir.path(), true).unwrap();
+        assert_eq!(std::fs::read_to_string(&scrollback).unwrap(), "$ nvim\n");
+        match (&config.windows[0], &config.windows[1]) {
+            (WindowConfig::WithLayout { window: editor }, WindowConfig::Complex { window }) => {
+                let editor = &editor["editor"];
+                assert_eq!(
+                    editor.panes[0].command(),
+                    format!("{print_scrollback}; nvim src/main.rs")
+                );
+                assert_eq!(
+                    editor.panes[2].command(),
+                    "",
+                    "only the name of cargo is known"
+                );
+                assert_eq!(window["server"], "cargo run");
+            }
+            other => panic!("Expected the editor and server windows, got {other:?}"),
+        }
+        assert_eq!(
+            snapshot.unstarted_programs(true),
+            vec!["editor, pane 2: cargo", "logs, pane 0: tail"]
+        );
+
+        let config = snapshot.to_restore_config(temp_dir.path(), false).unwrap();
+        match (&config.windows[0], &config.windows[1]) {
+            (WindowConfig::WithLayout { window: editor }, WindowConfig::Complex { window }) => {
+                assert_eq!(editor["editor"].panes[0].command(), print_scrollback);
+                assert_eq!(window["server"], "");
+            }
+            other => panic!("Expected the editor and server windows, got {other:?}"),
+        }
+        assert_eq!(snapshot.unstarted_programs(false), snapshot.programs());
+        assert_eq!(snapshot.programs()[0], "editor, pane 0: nvim src/main.rs");
+    }
+
+    #[test]
+    fn test_duplicate_window_names_are_made_unique() {
+        let mut windows = SessionSnapshot::parse_windows(
+            "0\tzsh\tcc00,160x48,0,0,0\n1\tzsh\tcc00,160x48,0,0,1\n\
+             2\tzsh-2\tcc00,160x48,0,0,2\n3\tzsh\tcc00,160x48,0,0,3\n",
+        )
+        .unwrap();
+        SessionSnapshot::add_panes(
+            &mut windows,
+            "0\t0\t/srv/app\t1\tzsh\n1\t0\t/srv/app\t2\tzsh\n\
+             2\t0\t/srv/app\t3\tzsh\n3\t0\t/srv/app\t4\tzsh\n",
+        )
+        .unwrap();
+        let snapshot = SessionSnapshot {
+            name: "app".to_string(),
+            root: PathBuf::from("/srv/app"),
+            windows,
+        };
+
+        let names: Vec<String> = snapshot
+            .to_config(false)
+            .windows
+            .iter()
+            .map(|window| match window {
+                WindowConfig::Complex { window } => window.keys().next().unwrap().clone(),
+                other => panic!("Expected Complex window, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(names, vec!["zsh", "zsh-3", "zsh-2", "zsh-4"]);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::error::{Result, TmuxrsError};
+use crate::snapshot::SessionSnapshot;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Version written to new state files; files with another version are rejected
+pub const STATE_VERSION: u32 = 1;
+
+/// Live sessions saved by `tmuxrs save` for `tmuxrs restore`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub version: u32,
+    pub sessions: Vec<SessionSnapshot>,
+}
+
+impl SavedState {
+    /// A state of the current version holding the given sessions
+    pub fn new(sessions: Vec<SessionSnapshot>) -> Self {
+        Self {
+            version: STATE_VERSION,
+            sessions,
+        }
+    }
+
+    /// Directory for tmuxrs state: `$XDG_STATE_HOME/tmuxrs`, or `~/.local/state/tmuxrs`
+    pub fn state_dir() -> Result<PathBuf> {
+        // Relative values are invalid per the XDG spec and are ignored
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute());
+        let state_home = match state_home {
+            Some(dir) => dir,
+            None => dirs::home_dir()
+                .ok_or_else(|| {
+                    TmuxrsError::ConfigNotFound("Could not find home directory".to_string())
+                })?
+                .join(".local")
+                .join("state"),
+        };
+        Ok(state_home.join("tmuxrs"))
+    }
+
+    /// The default state file inside `state_dir`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("sessions.json"))
+    }
+
+    /// Read a state file, rejecting versions this build does not understand
+    pub fn load(file_path: &Path) -> Result<Self> {
+        if !file_path.exists() {
+            return Err(TmuxrsError::NoSavedState(file_path.display().to_string()));
+        }
+
+        let content = std::fs::read_to_string(file_path)?;
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        let version = value.get("version").and_then(serde_json::Value::as_u64);
+        if version != Some(u64::from(STATE_VERSION)) {
+            return Err(TmuxrsError::UnsupportedStateVersion {
+                found: version.map_or_else(|| "none".to_string(), |v| v.to_string()),
+                supported: STATE_VERSION,
+            });
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Write the state file, creating its directory if needed
+    ///
+    /// The file is written next to its final path and then renamed, so an
+    /// interrupted save never leaves a truncated state behind.
+    pub fn write(&self, file_path: &Path) -> Result<()> {
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let partial = file_path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&partial, file_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{PaneSnapshot, WindowSnapshot};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_state_round_trips_and_checks_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("nested").join("sessions.json");
+
+        let state = SavedState::new(vec![SessionSnapshot {
+            name: "app".to_string(),
+            root: PathBuf::from("/srv/app"),
+            windows: vec![WindowSnapshot {
+                index: 0,
+                name: "editor".to_string(),
+                layout: None,
+                panes: vec![PaneSnapshot {
+                    index: 0,
+                    working_dir: PathBuf::from("/srv/app"),
+                    command: Some("vim".to_string()),
+                    command_line: Some("vim Makefile".to_string()),
+                    scrollback: Some("$ make\nok\n".to_string()),
+                    pid: None,
+                }],
+            }],
+        }]);
+        state.write(&file_path).unwrap();
+        assert_eq!(SavedState::load(&file_path).unwrap(), state);
+
+        std::fs::write(&file_path, r#"{"version": 99, "sessions": []}"#).unwrap();
+        match SavedState::load(&file_path) {
+            Err(TmuxrsError::UnsupportedStateVersion { found, supported }) => {
+                assert_eq!(found, "99");
+                assert_eq!(supported, STATE_VERSION);
+            }
+            other => panic!("Expected UnsupportedStateVersion, got {other:?}"),
+        }
+
+        assert!(matches!(
+            SavedState::load(&temp_dir.path().join("missing.json")),
+            Err(TmuxrsError::NoSavedState(_))
+        ));
+    }
+}
@@ -1,9 +1,68 @@
 use crate::error::{Result, TmuxrsError};
+use serde::Serialize;
+use std::fmt;
 use std::path::Path;
 use std::process::{Command, Stdio};
 
+/// Argument that separates chained commands in a single tmux invocation
+const SEPARATOR: &str = ";";
+
+/// `list-sessions` format parsed by `SessionInfo::parse_list`
+///
+/// The name comes last, so that tabs in it cannot shift the other fields.
+pub const SESSION_INFO_FORMAT: &str =
+    "#{session_attached}\t#{session_windows}\t#{session_created}\t#{session_name}";
+
+/// A running session, as reported by `list-sessions`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionInfo {
+    pub name: String,
+    /// Number of clients attached to the session
+    pub attached: usize,
+    pub windows: usize,
+    /// Creation time, in seconds since the Unix epoch
+    pub created: u64,
+}
+
+impl SessionInfo {
+    /// Parse `list-sessions` output in `SESSION_INFO_FORMAT`
+    pub fn parse_list(output: &str) -> Result<Vec<SessionInfo>> {
+        output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let unexpected =
+                    || TmuxrsError::TmuxError(format!("list-sessions: unexpected output '{line}'"));
+                let mut fields = line.splitn(4, '\t');
+                let mut number = || fields.next().and_then(|field| field.parse().ok());
+                let attached = number().ok_or_else(unexpected)?;
+                let windows = number().ok_or_else(unexpected)?;
+                let created = number().ok_or_else(unexpected)?;
+                let name = fields.next().ok_or_else(unexpected)?;
+                Ok(SessionInfo {
+                    name: name.to_string(),
+                    attached: attached as usize,
+                    windows: windows as usize,
+                    created,
+                })
+            })
+            .collect()
+    }
+
+    /// How long ago the session was created, e.g. `3d 4h` or `12m`
+    pub fn age(&self, now: u64) -> String {
+        let seconds = now.saturating_sub(self.created);
+        let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+        match (days, hours) {
+            (0, 0) => format!("{minutes}m"),
+            (0, _) => format!("{hours}h {minutes}m"),
+            _ => format!("{days}d {hours}h"),
+        }
+    }
+}
+
 /// Wrapper for tmux command execution
-#[derive(Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[allow(dead_code)]
 pub struct TmuxCommand {
     args: Vec<String>,
@@ -40,11 +99,74 @@ impl TmuxCommand {
         self
     }
 
+    /// The arguments passed to tmux, excluding the socket flag
+    #[allow(dead_code)]
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Chain several commands into a single tmux invocation
+    ///
+    /// tmux runs chained commands in order and stops at the first failure, just
+    /// as if they were run one at a time, but only one process is spawned. The
+    /// chain uses the socket of the first command. Arguments ending in `;` are
+    /// escaped so that tmux does not read them as separators.
+    #[allow(dead_code)]
+    pub fn chain<I: IntoIterator<Item = TmuxCommand>>(commands: I) -> Self {
+        let mut chained = Self::new();
+        for command in commands.into_iter().filter(|cmd| !cmd.args.is_empty()) {
+            if chained.args.is_empty() {
+                chained.socket_path = command.socket_path;
+            } else {
+                chained.args.push(SEPARATOR.to_string());
+            }
+            chained.args.extend(
+                command
+                    .args
+                    .into_iter()
+                    .map(|arg| match arg.strip_suffix(';') {
+                        Some(rest) => format!("{rest}\\;"),
+                        None => arg,
+                    }),
+            );
+        }
+        chained
+    }
+
+    /// Split the arguments into the commands tmux will run, the way tmux does
+    ///
+    /// An argument ending in `;` ends a command, unless the `;` is escaped as
+    /// `\;`, in which case it is kept as a literal `;`.
+    #[allow(dead_code)]
+    pub fn commands(&self) -> Vec<Vec<String>> {
+        let mut commands = vec![Vec::new()];
+        for arg in &self.args {
+            match arg.strip_suffix(';') {
+                Some(rest) if rest.ends_with('\\') => {
+                    let literal = format!("{};", &rest[..rest.len() - 1]);
+                    commands.last_mut().unwrap().push(literal);
+                }
+                Some(rest) => {
+                    if !rest.is_empty() {
+                        commands.last_mut().unwrap().push(rest.to_string());
+                    }
+                    commands.push(Vec::new());
+                }
+                None => commands.last_mut().unwrap().push(arg.clone()),
+            }
+        }
+        commands.retain(|command| !command.is_empty());
+        commands
+    }
+
     /// Execute the tmux command (non-interactive)
     #[allow(dead_code)]
     pub fn execute(self) -> Result<String> {
         let mut cmd = Command::new("tmux");
 
+        // Without a UTF-8 locale tmux prints the tabs of `-F` formats as `_`
+        cmd.arg("-u");
+
         // Add socket path if specified
         if let Some(socket) = &self.socket_path {
             cmd.args(["-S", socket]);
@@ -102,6 +224,22 @@ impl TmuxCommand {
         Ok(())
     }
 
+    /// Quote an argument so that both sh and tmux's command parser read it verbatim
+    ///
+    /// Arguments made only of safe characters are returned unchanged.
+    #[allow(dead_code)]
+    pub fn quote(arg: &str) -> String {
+        let is_safe = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+        if is_safe {
+            arg.to_string()
+        } else {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        }
+    }
+
     /// Check if TTY is available for interactive operations
     #[allow(dead_code)]
     fn is_tty_available() -> bool {
@@ -115,13 +253,25 @@ impl TmuxCommand {
         Self::session_exists_with_socket(session_name, None::<&Path>)
     }
 
+    /// Build the command that checks whether a session exists without running it
+    ///
+    /// The name is matched exactly (`=name`), so that `acme` is not taken to
+    /// exist just because `acme@feature-x` does.
+    #[allow(dead_code)]
+    pub fn has_session_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("has-session")
+            .arg("-t")
+            .arg(format!("={session_name}"))
+    }
+
     /// Check if a session exists using a specific socket
     #[allow(dead_code)]
     pub fn session_exists_with_socket<P: AsRef<Path>>(
         session_name: &str,
         socket_path: Option<P>,
     ) -> Result<bool> {
-        let mut cmd = Self::new().arg("has-session").arg("-t").arg(session_name);
+        let mut cmd = Self::has_session_command(session_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -136,6 +286,31 @@ impl TmuxCommand {
         }
     }
 
+    /// Build the command that prints a pane's history and visible contents without running it
+    #[allow(dead_code)]
+    pub fn capture_pane_command(session_name: &str, window_name: &str, pane_index: usize) -> Self {
+        Self::new()
+            .arg("capture-pane")
+            .arg("-p") // Print to stdout instead of a paste buffer
+            .arg("-J") // Join wrapped lines
+            .arg("-t")
+            .arg(format!("{session_name}:{window_name}.{pane_index}"))
+            .arg("-S")
+            .arg("-") // From the start of the history
+    }
+
+    /// Build the command to create a new tmux session without running it
+    #[allow(dead_code)]
+    pub fn new_session_command(session_name: &str, working_dir: &Path) -> Self {
+        Self::new()
+            .arg("new-session")
+            .arg("-d") // Detached
+            .arg("-s")
+            .arg(session_name)
+            .arg("-c")
+            .arg(working_dir.to_string_lossy().as_ref())
+    }
+
     /// Create a new tmux session
     #[allow(dead_code)]
     pub fn new_session(session_name: &str, working_dir: &Path) -> Result<String> {
@@ -149,21 +324,35 @@ impl TmuxCommand {
         working_dir: &Path,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
-            .arg("new-session")
-            .arg("-d") // Detached
-            .arg("-s")
-            .arg(session_name)
-            .arg("-c")
-            .arg(working_dir.to_string_lossy().as_ref());
+        let mut cmd = Self::new_session_command(session_name, working_dir);
 
-        if let Some(socket) = socket_path.as_ref() {
+        if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
         }
 
         cmd.execute()
     }
 
+    /// Build the command to set base-index to 0 for a session without running it
+    #[allow(dead_code)]
+    pub fn set_base_index_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("set-option")
+            .arg("-t")
+            .arg(session_name)
+            .arg("base-index")
+            .arg("0")
+    }
+
+    /// Build the command that renumbers a session's windows from its base-index without running it
+    pub fn renumber_windows_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("move-window")
+            .arg("-r")
+            .arg("-t")
+            .arg(session_name)
+    }
+
     /// Set base-index to 0 for a session
     #[allow(dead_code)]
     pub fn set_base_index(session_name: &str) -> Result<String> {
@@ -176,12 +365,7 @@ impl TmuxCommand {
         session_name: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
-            .arg("set-option")
-            .arg("-t")
-            .arg(session_name)
-            .arg("base-index")
-            .arg("0");
+        let mut cmd = Self::set_base_index_command(session_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -190,6 +374,17 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to set pane-base-index to 0 for a session without running it
+    #[allow(dead_code)]
+    pub fn set_pane_base_index_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("set-option")
+            .arg("-t")
+            .arg(session_name)
+            .arg("pane-base-index")
+            .arg("0")
+    }
+
     /// Set pane-base-index to 0 for a session
     #[allow(dead_code)]
     pub fn set_pane_base_index(session_name: &str) -> Result<String> {
@@ -202,12 +397,7 @@ impl TmuxCommand {
         session_name: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
-            .arg("set-option")
-            .arg("-t")
-            .arg(session_name)
-            .arg("pane-base-index")
-            .arg("0");
+        let mut cmd = Self::set_pane_base_index_command(session_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -250,6 +440,98 @@ impl TmuxCommand {
         Ok(first_index.to_string())
     }
 
+    /// Build the command that lists the window names of a session without running it
+    #[allow(dead_code)]
+    pub fn list_window_names_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("list-windows")
+            .arg("-t")
+            .arg(session_name)
+            .arg("-F")
+            .arg("#{window_name}")
+    }
+
+    /// List the names of all windows in a session
+    #[allow(dead_code)]
+    pub fn list_window_names(session_name: &str) -> Result<Vec<String>> {
+        Self::list_window_names_with_socket(session_name, None::<&Path>)
+    }
+
+    /// List the names of all windows in a session using a specific socket
+    #[allow(dead_code)]
+    pub fn list_window_names_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        socket_path: Option<P>,
+    ) -> Result<Vec<String>> {
+        let mut cmd = Self::list_window_names_command(session_name);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
+        }
+
+        let output = cmd.execute()?;
+        Ok(output.lines().map(|line| line.trim().to_string()).collect())
+    }
+
+    /// Build the command that lists every session, one line per session in `format`
+    #[allow(dead_code)]
+    pub fn list_sessions_command(format: &str) -> Self {
+        Self::new().arg("list-sessions").arg("-F").arg(format)
+    }
+
+    /// List every running session
+    #[allow(dead_code)]
+    pub fn list_sessions() -> Result<Vec<SessionInfo>> {
+        Self::list_sessions_with_socket(None::<&Path>)
+    }
+
+    /// List every running session using a specific socket
+    #[allow(dead_code)]
+    pub fn list_sessions_with_socket<P: AsRef<Path>>(
+        socket_path: Option<P>,
+    ) -> Result<Vec<SessionInfo>> {
+        let mut cmd = Self::list_sessions_command(SESSION_INFO_FORMAT);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
+        }
+
+        SessionInfo::parse_list(&cmd.execute()?)
+    }
+
+    /// Build the command that lists the windows of a session, one line per window in `format`
+    #[allow(dead_code)]
+    pub fn list_windows_command(session_name: &str, format: &str) -> Self {
+        Self::new()
+            .arg("list-windows")
+            .arg("-t")
+            .arg(session_name)
+            .arg("-F")
+            .arg(format)
+    }
+
+    /// Build the command that lists the panes of a window, one line per pane in `format`
+    #[allow(dead_code)]
+    pub fn list_panes_command(session_name: &str, window_target: &str, format: &str) -> Self {
+        Self::new()
+            .arg("list-panes")
+            .arg("-t")
+            .arg(format!("{session_name}:{window_target}"))
+            .arg("-F")
+            .arg(format)
+    }
+
+    /// Build the command to rename a window in a session without running it
+    #[allow(dead_code)]
+    pub fn rename_window_command(session_name: &str, window_target: &str, new_name: &str) -> Self {
+        let target = format!("{session_name}:{window_target}");
+        Self::new()
+            .arg("rename-window")
+            .arg("-t")
+            .arg(target)
+            .arg(new_name)
+    }
+
     /// Rename a window in a session
     #[allow(dead_code)]
     pub fn rename_window(
@@ -268,12 +550,7 @@ impl TmuxCommand {
         new_name: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let target = format!("{session_name}:{window_target}");
-        let mut cmd = Self::new()
-            .arg("rename-window")
-            .arg("-t")
-            .arg(target)
-            .arg(new_name);
+        let mut cmd = Self::rename_window_command(session_name, window_target, new_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -282,6 +559,33 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to create a new window in a session without running it
+    #[allow(dead_code)]
+    pub fn new_window_command(
+        session_name: &str,
+        window_name: &str,
+        command: Option<&str>,
+        working_dir: Option<&Path>,
+    ) -> Self {
+        let mut cmd = Self::new()
+            .arg("new-window")
+            .arg("-t")
+            .arg(session_name)
+            .arg("-n")
+            .arg(window_name);
+
+        // Add working directory if provided
+        if let Some(dir) = working_dir {
+            cmd = cmd.arg("-c").arg(dir.to_string_lossy().as_ref());
+        }
+
+        if let Some(cmd_str) = command {
+            cmd = cmd.arg(cmd_str);
+        }
+
+        cmd
+    }
+
     /// Create a new window in a session
     #[allow(dead_code)]
     pub fn new_window(
@@ -308,21 +612,7 @@ impl TmuxCommand {
         working_dir: Option<&Path>,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
-            .arg("new-window")
-            .arg("-t")
-            .arg(session_name)
-            .arg("-n")
-            .arg(window_name);
-
-        // Add working directory if provided
-        if let Some(dir) = working_dir {
-            cmd = cmd.arg("-c").arg(dir.to_string_lossy().as_ref());
-        }
-
-        if let Some(cmd_str) = command {
-            cmd = cmd.arg(cmd_str);
-        }
+        let mut cmd = Self::new_window_command(session_name, window_name, command, working_dir);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -331,6 +621,18 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to send keys to a window without running it
+    #[allow(dead_code)]
+    pub fn send_keys_command(session_name: &str, window_name: &str, keys: &str) -> Self {
+        let target = format!("{session_name}:{window_name}");
+        Self::new()
+            .arg("send-keys")
+            .arg("-t")
+            .arg(target)
+            .arg(keys)
+            .arg("Enter")
+    }
+
     /// Send keys to a window
     #[allow(dead_code)]
     pub fn send_keys(session_name: &str, window_name: &str, keys: &str) -> Result<String> {
@@ -345,13 +647,7 @@ impl TmuxCommand {
         keys: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let target = format!("{session_name}:{window_name}");
-        let mut cmd = Self::new()
-            .arg("send-keys")
-            .arg("-t")
-            .arg(target)
-            .arg(keys)
-            .arg("Enter");
+        let mut cmd = Self::send_keys_command(session_name, window_name, keys);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -360,6 +656,23 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to send keys to a specific pane without running it
+    #[allow(dead_code)]
+    pub fn send_keys_to_pane_command(
+        session_name: &str,
+        window_name: &str,
+        pane_index: usize,
+        keys: &str,
+    ) -> Self {
+        let target = format!("{session_name}:{window_name}.{pane_index}");
+        Self::new()
+            .arg("send-keys")
+            .arg("-t")
+            .arg(target)
+            .arg(keys)
+            .arg("Enter")
+    }
+
     /// Send keys to a specific pane
     #[allow(dead_code)]
     pub fn send_keys_to_pane(
@@ -386,13 +699,7 @@ impl TmuxCommand {
         keys: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let target = format!("{session_name}:{window_name}.{pane_index}");
-        let mut cmd = Self::new()
-            .arg("send-keys")
-            .arg("-t")
-            .arg(target)
-            .arg(keys)
-            .arg("Enter");
+        let mut cmd = Self::send_keys_to_pane_command(session_name, window_name, pane_index, keys);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -401,6 +708,12 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to kill a session without running it
+    #[allow(dead_code)]
+    pub fn kill_session_command(session_name: &str) -> Self {
+        Self::new().arg("kill-session").arg("-t").arg(session_name)
+    }
+
     /// Kill a session
     #[allow(dead_code)]
     pub fn kill_session(session_name: &str) -> Result<String> {
@@ -413,7 +726,7 @@ impl TmuxCommand {
         session_name: &str,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new().arg("kill-session").arg("-t").arg(session_name);
+        let mut cmd = Self::kill_session_command(session_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -422,6 +735,38 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to split a window horizontally without running it
+    #[allow(dead_code)]
+    pub fn split_window_horizontal_command(
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+        working_dir: Option<&Path>,
+    ) -> Self {
+        let target = if window_name.is_empty() {
+            session_name.to_string()
+        } else {
+            format!("{session_name}:{window_name}")
+        };
+        let mut cmd = Self::new()
+            .arg("split-window")
+            .arg("-h") // horizontal split (side by side)
+            .arg("-t")
+            .arg(&target);
+
+        // Add working directory if provided
+        if let Some(dir) = working_dir {
+            cmd = cmd.arg("-c").arg(dir.to_string_lossy().as_ref());
+        }
+
+        // Only add command if not empty - this allows shell to initialize properly
+        if !command.trim().is_empty() {
+            cmd = cmd.arg(command);
+        }
+
+        cmd
+    }
+
     /// Split window horizontally (side by side)
     #[allow(dead_code)]
     pub fn split_window_horizontal(
@@ -448,16 +793,69 @@ impl TmuxCommand {
         working_dir: Option<&Path>,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let target = if window_name.is_empty() {
-            session_name.to_string()
-        } else {
-            format!("{session_name}:{window_name}")
+        let mut cmd =
+            Self::split_window_horizontal_command(session_name, window_name, command, working_dir);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
+        }
+
+        cmd.execute()
+    }
+
+    /// Build the command to split a pane without running it
+    ///
+    /// Splits the given pane, or the active pane of the window when `pane_index`
+    /// is `None`. `vertical` puts the new pane below instead of beside it, and
+    /// `size` is passed to `-l` as cells (`20`) or a percentage (`30%`).
+    #[allow(dead_code)]
+    pub fn split_pane_command(
+        session_name: &str,
+        window_name: &str,
+        pane_index: Option<usize>,
+        vertical: bool,
+        size: Option<&str>,
+        working_dir: Option<&Path>,
+    ) -> Self {
+        let target = match pane_index {
+            Some(pane_index) => format!("{session_name}:{window_name}.{pane_index}"),
+            None => format!("{session_name}:{window_name}"),
         };
         let mut cmd = Self::new()
             .arg("split-window")
-            .arg("-h") // horizontal split (side by side)
+            .arg(if vertical { "-v" } else { "-h" })
             .arg("-t")
-            .arg(&target);
+            .arg(target);
+
+        if let Some(size) = size {
+            cmd = cmd.arg("-l").arg(size);
+        }
+
+        // Add working directory if provided
+        if let Some(dir) = working_dir {
+            cmd = cmd.arg("-c").arg(dir.to_string_lossy().as_ref());
+        }
+
+        cmd
+    }
+
+    /// Build the command to split a window vertically without running it
+    #[allow(dead_code)]
+    pub fn split_window_vertical_command(
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+        working_dir: Option<&Path>,
+    ) -> Self {
+        let mut cmd = Self::new()
+            .arg("split-window")
+            .arg("-v") // vertical split (above/below)
+            .arg("-t")
+            .arg(if window_name.is_empty() {
+                session_name.to_string()
+            } else {
+                format!("{session_name}:{window_name}")
+            });
 
         // Add working directory if provided
         if let Some(dir) = working_dir {
@@ -469,11 +867,7 @@ impl TmuxCommand {
             cmd = cmd.arg(command);
         }
 
-        if let Some(socket) = socket_path {
-            cmd = cmd.socket(socket);
-        }
-
-        cmd.execute()
+        cmd
     }
 
     /// Split window vertically (above/below)
@@ -502,26 +896,68 @@ impl TmuxCommand {
         working_dir: Option<&Path>,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
-            .arg("split-window")
-            .arg("-v") // vertical split (above/below)
-            .arg("-t")
-            .arg(if window_name.is_empty() {
-                session_name.to_string()
-            } else {
-                format!("{session_name}:{window_name}")
-            });
+        let mut cmd =
+            Self::split_window_vertical_command(session_name, window_name, command, working_dir);
 
-        // Add working directory if provided
-        if let Some(dir) = working_dir {
-            cmd = cmd.arg("-c").arg(dir.to_string_lossy().as_ref());
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
         }
 
-        // Only add command if not empty - this allows shell to initialize properly
-        if !command.trim().is_empty() {
-            cmd = cmd.arg(command);
+        cmd.execute()
+    }
+
+    /// Build the command to select (make active) a window in a session without running it
+    #[allow(dead_code)]
+    pub fn select_window_command(session_name: &str, window_name: &str) -> Self {
+        let target = format!("{session_name}:{window_name}");
+        Self::new().arg("select-window").arg("-t").arg(target)
+    }
+
+    /// Select (make active) a window in a session
+    #[allow(dead_code)]
+    pub fn select_window(session_name: &str, window_name: &str) -> Result<String> {
+        Self::select_window_with_socket(session_name, window_name, None::<&Path>)
+    }
+
+    /// Select (make active) a window in a session using a specific socket
+    #[allow(dead_code)]
+    pub fn select_window_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        window_name: &str,
+        socket_path: Option<P>,
+    ) -> Result<String> {
+        let mut cmd = Self::select_window_command(session_name, window_name);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
         }
 
+        cmd.execute()
+    }
+
+    /// Build the command to select (make active) a pane in a window without running it
+    #[allow(dead_code)]
+    pub fn select_pane_command(session_name: &str, window_name: &str, pane_index: usize) -> Self {
+        let target = format!("{session_name}:{window_name}.{pane_index}");
+        Self::new().arg("select-pane").arg("-t").arg(target)
+    }
+
+    /// Select (make active) a pane in a window
+    #[allow(dead_code)]
+    pub fn select_pane(session_name: &str, window_name: &str, pane_index: usize) -> Result<String> {
+        Self::select_pane_with_socket(session_name, window_name, pane_index, None::<&Path>)
+    }
+
+    /// Select (make active) a pane in a window using a specific socket
+    #[allow(dead_code)]
+    pub fn select_pane_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        window_name: &str,
+        pane_index: usize,
+        socket_path: Option<P>,
+    ) -> Result<String> {
+        let mut cmd = Self::select_pane_command(session_name, window_name, pane_index);
+
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
         }
@@ -529,21 +965,64 @@ impl TmuxCommand {
         cmd.execute()
     }
 
-    /// Select layout for a window
+    /// Build the command to restart a pane's shell in another directory without running it
     #[allow(dead_code)]
-    pub fn select_layout(session_name: &str, window_name: &str, layout: &str) -> Result<String> {
-        Self::select_layout_with_socket(session_name, window_name, layout, None::<&Path>)
+    pub fn respawn_pane_command(
+        session_name: &str,
+        window_name: &str,
+        pane_index: usize,
+        working_dir: &Path,
+    ) -> Self {
+        let target = format!("{session_name}:{window_name}.{pane_index}");
+        Self::new()
+            .arg("respawn-pane")
+            .arg("-k") // Kill the current shell first
+            .arg("-t")
+            .arg(target)
+            .arg("-c")
+            .arg(working_dir.to_string_lossy().as_ref())
     }
 
-    /// Select layout for a window using a specific socket
+    /// Restart a pane's shell in a different working directory
     #[allow(dead_code)]
-    pub fn select_layout_with_socket<P: AsRef<Path>>(
+    pub fn respawn_pane(
         session_name: &str,
         window_name: &str,
-        layout: &str,
+        pane_index: usize,
+        working_dir: &Path,
+    ) -> Result<String> {
+        Self::respawn_pane_with_socket(
+            session_name,
+            window_name,
+            pane_index,
+            working_dir,
+            None::<&Path>,
+        )
+    }
+
+    /// Restart a pane's shell in a different working directory using a specific socket
+    #[allow(dead_code)]
+    pub fn respawn_pane_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        window_name: &str,
+        pane_index: usize,
+        working_dir: &Path,
         socket_path: Option<P>,
     ) -> Result<String> {
-        let mut cmd = Self::new()
+        let mut cmd =
+            Self::respawn_pane_command(session_name, window_name, pane_index, working_dir);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
+        }
+
+        cmd.execute()
+    }
+
+    /// Build the command to select the layout of a window without running it
+    #[allow(dead_code)]
+    pub fn select_layout_command(session_name: &str, window_name: &str, layout: &str) -> Self {
+        Self::new()
             .arg("select-layout")
             .arg("-t")
             .arg(if window_name.is_empty() {
@@ -551,7 +1030,24 @@ impl TmuxCommand {
             } else {
                 format!("{session_name}:{window_name}")
             })
-            .arg(layout);
+            .arg(layout)
+    }
+
+    /// Select layout for a window
+    #[allow(dead_code)]
+    pub fn select_layout(session_name: &str, window_name: &str, layout: &str) -> Result<String> {
+        Self::select_layout_with_socket(session_name, window_name, layout, None::<&Path>)
+    }
+
+    /// Select layout for a window using a specific socket
+    #[allow(dead_code)]
+    pub fn select_layout_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        window_name: &str,
+        layout: &str,
+        socket_path: Option<P>,
+    ) -> Result<String> {
+        let mut cmd = Self::select_layout_command(session_name, window_name, layout);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -560,6 +1056,49 @@ impl TmuxCommand {
         cmd.execute()
     }
 
+    /// Build the command to set a session hook without running it
+    #[allow(dead_code)]
+    pub fn set_hook_command(session_name: &str, hook_name: &str, command: &str) -> Self {
+        Self::new()
+            .arg("set-hook")
+            .arg("-t")
+            .arg(session_name)
+            .arg(hook_name)
+            .arg(command)
+    }
+
+    /// Set a session hook (e.g. `client-detached`) to run a tmux command
+    #[allow(dead_code)]
+    pub fn set_hook(session_name: &str, hook_name: &str, command: &str) -> Result<String> {
+        Self::set_hook_with_socket(session_name, hook_name, command, None::<&Path>)
+    }
+
+    /// Set a session hook using a specific socket
+    #[allow(dead_code)]
+    pub fn set_hook_with_socket<P: AsRef<Path>>(
+        session_name: &str,
+        hook_name: &str,
+        command: &str,
+        socket_path: Option<P>,
+    ) -> Result<String> {
+        let mut cmd = Self::set_hook_command(session_name, hook_name, command);
+
+        if let Some(socket) = socket_path {
+            cmd = cmd.socket(socket);
+        }
+
+        cmd.execute()
+    }
+
+    /// Build the command to attach to a session without running it
+    #[allow(dead_code)]
+    pub fn attach_session_command(session_name: &str) -> Self {
+        Self::new()
+            .arg("attach-session")
+            .arg("-t")
+            .arg(session_name)
+    }
+
     /// Attach to a session (interactive)
     #[allow(dead_code)]
     pub fn attach_session(session_name: &str) -> Result<()> {
@@ -572,10 +1111,7 @@ impl TmuxCommand {
         session_name: &str,
         socket_path: Option<P>,
     ) -> Result<()> {
-        let mut cmd = Self::new()
-            .arg("attach-session")
-            .arg("-t")
-            .arg(session_name);
+        let mut cmd = Self::attach_session_command(session_name);
 
         if let Some(socket) = socket_path {
             cmd = cmd.socket(socket);
@@ -603,11 +1139,70 @@ impl TmuxCommand {
     }
 }
 
+/// Renders the command as a shell-ready command line, e.g. `tmux -S /tmp/s kill-session -t 'my app'`
+///
+/// Chained commands are separated by `\;`. The alternate form (`{:#}`) puts
+/// each command of a chain on its own continuation line.
+impl fmt::Display for TmuxCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tmux")?;
+        if let Some(socket) = &self.socket_path {
+            write!(f, " -S {}", Self::quote(socket))?;
+        }
+
+        let segments: Vec<&[String]> = self.args.split(|arg| arg == SEPARATOR).collect();
+        let multiline = f.alternate() && segments.len() > 1;
+        for (index, segment) in segments.iter().enumerate() {
+            if index > 0 {
+                write!(f, " \\;")?;
+            }
+            if multiline {
+                write!(f, " \\\n ")?;
+            }
+            for arg in segment.iter() {
+                write!(f, " {}", Self::quote(arg))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_parse_session_info() {
+        let sessions =
+            SessionInfo::parse_list("1\t3\t1700000000\tweb\n0\t1\t1700003600\twith\ttab\n")
+                .unwrap();
+        assert_eq!(
+            sessions,
+            vec![
+                SessionInfo {
+                    name: "web".to_string(),
+                    attached: 1,
+                    windows: 3,
+                    created: 1_700_000_000,
+                },
+                SessionInfo {
+                    name: "with\ttab".to_string(),
+                    attached: 0,
+                    windows: 1,
+                    created: 1_700_003_600,
+                },
+            ]
+        );
+        assert!(SessionInfo::parse_list("web\t1").is_err());
+
+        let session = &sessions[0];
+        assert_eq!(session.age(1_700_000_000 + 125), "2m");
+        assert_eq!(session.age(1_700_000_000 + 3 * 3600 + 120), "3h 2m");
+        assert_eq!(session.age(1_700_000_000 + 2 * 86400 + 3600), "2d 1h");
+        assert_eq!(session.age(0), "0m", "clock skew is not an error");
+    }
+
     #[test]
     fn test_tmux_command_builder_basic() {
         let cmd = TmuxCommand::new().arg("list-sessions");
@@ -712,6 +1307,81 @@ mod tests {
         assert_eq!(target, "test-session");
     }
 
+    #[test]
+    fn test_quote() {
+        assert_eq!(TmuxCommand::quote("main-vertical"), "main-vertical");
+        assert_eq!(TmuxCommand::quote("/tmp/my.sock"), "/tmp/my.sock");
+        assert_eq!(TmuxCommand::quote(""), "''");
+        assert_eq!(TmuxCommand::quote("echo hi"), "'echo hi'");
+        assert_eq!(TmuxCommand::quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_display_renders_command_line() {
+        let cmd = TmuxCommand::send_keys_to_pane_command("my app", "editor", 1, "echo 'hi'")
+            .socket("/tmp/tmux.sock");
+        assert_eq!(
+            cmd.to_string(),
+            r#"tmux -S /tmp/tmux.sock send-keys -t 'my app:editor.1' 'echo '\''hi'\''' Enter"#
+        );
+
+        let cmd = TmuxCommand::new_session_command("proj", Path::new("/home/me/proj"));
+        assert_eq!(
+            cmd.to_string(),
+            "tmux new-session -d -s proj -c /home/me/proj"
+        );
+    }
+
+    #[test]
+    fn test_chain_joins_commands_with_separators() {
+        let cmd = TmuxCommand::chain([
+            TmuxCommand::new_session_command("proj", Path::new("/srv/proj")).socket("/tmp/s"),
+            TmuxCommand::send_keys_command("proj", "editor", "find . -exec rm {} \\;"),
+            TmuxCommand::send_keys_command("proj", "editor", "cd src;"),
+        ]);
+
+        assert_eq!(
+            cmd.to_string(),
+            r"tmux -S /tmp/s new-session -d -s proj -c /srv/proj \; send-keys -t proj:editor 'find . -exec rm {} \\;' Enter \; send-keys -t proj:editor 'cd src\;' Enter"
+        );
+        assert_eq!(
+            format!("{cmd:#}").lines().collect::<Vec<_>>(),
+            vec![
+                r"tmux -S /tmp/s \",
+                r"  new-session -d -s proj -c /srv/proj \; \",
+                r"  send-keys -t proj:editor 'find . -exec rm {} \\;' Enter \; \",
+                r"  send-keys -t proj:editor 'cd src\;' Enter",
+            ]
+        );
+
+        // Splitting undoes the escaping, so each command gets its original arguments back
+        assert_eq!(
+            cmd.commands(),
+            vec![
+                TmuxCommand::new_session_command("proj", Path::new("/srv/proj")).args,
+                TmuxCommand::send_keys_command("proj", "editor", "find . -exec rm {} \\;").args,
+                TmuxCommand::send_keys_command("proj", "editor", "cd src;").args,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commands_splits_like_tmux() {
+        let cmd = TmuxCommand::new()
+            .arg("kill-session")
+            .arg("-t")
+            .arg("a;")
+            .arg("kill-server");
+        assert_eq!(
+            cmd.commands(),
+            vec![vec!["kill-session", "-t", "a"], vec!["kill-server"]]
+        );
+
+        let single = TmuxCommand::kill_session_command("app");
+        assert_eq!(single.commands(), vec![single.args.clone()]);
+        assert_eq!(format!("{single:#}"), single.to_string());
+    }
+
     #[test]
     fn test_command_trimming() {
         // Test that commands are properly trimmed
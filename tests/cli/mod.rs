@@ -1,5 +1,6 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
+use std::path::Path;
 use tmuxrs::config::Config;
 use tmuxrs::session::SessionManager;
 use tmuxrs::tmux::TmuxCommand;
@@ -82,6 +83,362 @@ fn test_start_with_append_flag_parsing() {
         .stderr(predicate::str::contains("Configuration file not found"));
 }
 
+#[test]
+fn test_debug_prints_plan_without_tmux() {
+    let home = tempfile::TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("debug-me.yml"),
+        r#"
+name: debug-me
+root: /tmp
+windows:
+  - editor: vim
+  - server: echo "server starting"
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .arg("debug")
+        .arg("debug-me")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "tmux \\\n  new-session -d -s debug-me -c /tmp \\; \\\n",
+        ))
+        .stdout(predicate::str::contains(
+            "\n  send-keys -t debug-me:1 'echo \"server starting\"' Enter\n",
+        ))
+        .stdout(predicate::str::ends_with(
+            "tmux attach-session -t debug-me\n",
+        ));
+}
+
+#[test]
+fn test_dry_run_prints_plan_without_tmux() {
+    let home = tempfile::TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("dry-run-me.yml"),
+        r#"
+name: dry-run-me
+root: /tmp
+on_project_stop: echo bye
+windows:
+  - editor: vim
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .args(["start", "dry-run-me", "--no-attach", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "tmux has-session -t =dry-run-me\ntmux \\\n  new-session -d -s dry-run-me -c /tmp \\; \\\n",
+        ))
+        .stdout(predicate::str::contains("attach-session").not());
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    let output = cmd
+        .env("HOME", home.path())
+        .args(["stop", "dry-run-me", "--dry-run", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let steps: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        steps[1]["command"]["args"],
+        serde_json::json!(["kill-session", "-t", "dry-run-me"])
+    );
+    assert_eq!(steps[2]["type"], "hook");
+    assert_eq!(steps[2]["hook"], "on_project_stop");
+    assert_eq!(steps[2]["command"], "echo bye");
+}
+
+#[test]
+fn test_new_and_edit_reparse_config_after_editor() {
+    let home = tempfile::TempDir::new().unwrap();
+    let project = home.path().join("code").join("scaffolded");
+    std::fs::create_dir_all(&project).unwrap();
+    let config_file = home
+        .path()
+        .join(".config")
+        .join("tmuxrs")
+        .join("scaffolded.yml");
+
+    // `true` leaves the template as written
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .env("VISUAL", "true")
+        .current_dir(&project)
+        .args(["new", "scaffolded"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Configuration 'scaffolded' (2 windows) saved to",
+        ));
+    let template = std::fs::read_to_string(&config_file).unwrap();
+    assert!(template.contains("root: ~/code/scaffolded"));
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .env("VISUAL", "true")
+        .args(["new", "scaffolded"])
+        .assert()
+        .failure();
+
+    // An edit that breaks the YAML is reported with the offending line
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .env("VISUAL", "sed -i 's/layout:.*/layout: [oops/'")
+        .args(["edit", "scaffolded"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Invalid configuration file {}:",
+            config_file.display()
+        )))
+        .stderr(predicate::str::contains("while parsing a flow sequence"))
+        .stderr(predicate::str::contains("23 |       panes:"));
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .env("VISUAL", "true")
+        .args(["edit", "missing"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_list_reports_invalid_configs() {
+    let home = tempfile::TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("good.yml"),
+        "name: good\nroot: ~/good\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+    std::fs::write(
+        config_dir.join("typo.yml"),
+        "name: typo\nwindows:\n  - editor: [vim\n  - server: rails s\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  good - ~/good (1 windows)"))
+        .stdout(predicate::str::contains("Invalid configurations:"))
+        .stdout(predicate::str::contains(format!(
+            "  {}:4:11: did not find expected ',' or ']'",
+            config_dir.join("typo.yml").display()
+        )));
+
+    let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+    cmd.env("HOME", home.path())
+        .args(["list", "--strict"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Invalid configurations:"));
+}
+
+#[test]
+fn test_config_dir_flag_and_environment() {
+    let home = tempfile::TempDir::new().unwrap();
+    let write_config = |dir: &Path, name: &str| {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{name}.yml")),
+            format!("name: {name}\nwindows:\n  - editor: vim\n"),
+        )
+        .unwrap();
+    };
+    let xdg = home.path().join("xdg");
+    let custom = home.path().join("custom");
+    let flagged = home.path().join("flagged");
+    write_config(&home.path().join(".config").join("tmuxrs"), "default");
+    write_config(&xdg.join("tmuxrs"), "from-xdg");
+    write_config(&custom, "from-env");
+    write_config(&flagged, "from-flag");
+
+    let list = |envs: &[(&str, &Path)], args: &[&str]| {
+        let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+        cmd.env("HOME", home.path())
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TMUXRS_CONFIG_DIR");
+        for (name, value) in envs {
+            cmd.env(name, value);
+        }
+        let output = cmd.arg("list").args(args).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(list(&[], &[]).contains("  default - ~"));
+    assert!(list(&[("XDG_CONFIG_HOME", &xdg)], &[]).contains("  from-xdg - ~"));
+    assert!(list(
+        &[("XDG_CONFIG_HOME", &xdg), ("TMUXRS_CONFIG_DIR", &custom)],
+        &[]
+    )
+    .contains("  from-env - ~"));
+    assert!(list(
+        &[("TMUXRS_CONFIG_DIR", &custom)],
+        &["--config-dir", flagged.to_str().unwrap()]
+    )
+    .contains("  from-flag - ~"));
+}
+
+#[test]
+fn test_configs_are_found_in_tmuxinator_and_extra_directories() {
+    let home = tempfile::TempDir::new().unwrap();
+    let write_config = |dir: &Path, name: &str, root: &str| {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{name}.yml")),
+            format!("name: {name}\nroot: {root}\nwindows:\n  - editor: vim\n"),
+        )
+        .unwrap();
+    };
+    let tmuxrs_dir = home.path().join(".config").join("tmuxrs");
+    let tmuxinator_dir = home.path().join(".tmuxinator");
+    let extra_dir = home.path().join("shared");
+    write_config(&tmuxrs_dir, "web", "/srv/web");
+    write_config(&tmuxinator_dir, "web", "/old/web");
+    write_config(&tmuxinator_dir, "api", "/srv/api");
+    write_config(&extra_dir, "docs", "/srv/docs");
+    std::fs::write(
+        tmuxrs_dir.join(".settings.yml"),
+        "config_dirs:\n  - ~/shared\n",
+    )
+    .unwrap();
+
+    let tmuxrs = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+        cmd.env("HOME", home.path())
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TMUXRS_CONFIG_DIR")
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    tmuxrs(&["list"]).stdout(predicate::str::diff(
+        "Available configurations:\n  \
+           ~/.config/tmuxrs/\n    \
+             web - /srv/web (1 windows)\n  \
+           ~/.tmuxinator/\n    \
+             api - /srv/api (1 windows)\n    \
+             web - /old/web (1 windows) [shadowed by ~/.config/tmuxrs/web.yml]\n  \
+           ~/shared/\n    \
+             docs - /srv/docs (1 windows)\n",
+    ));
+    tmuxrs(&["list", "--format", "names"]).stdout(predicate::str::diff("web\napi\ndocs\n"));
+
+    // Sessions are started from the first config of that name
+    tmuxrs(&["debug", "web"]).stdout(predicate::str::contains("/srv/web"));
+    tmuxrs(&["debug", "api"]).stdout(predicate::str::contains("/srv/api"));
+    tmuxrs(&["debug", "docs"]).stdout(predicate::str::contains("/srv/docs"));
+}
+
+#[test]
+fn test_debug_without_name_matches_config_root() {
+    let home = tempfile::TempDir::new().unwrap();
+    let home_path = std::fs::canonicalize(home.path()).unwrap();
+    let web = home_path.join("work").join("acme").join("web");
+    std::fs::create_dir_all(web.join("src")).unwrap();
+    let config_dir = home_path.join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("acme-web.yml"),
+        "name: acme-web\nroot: ~/work/acme/web\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+
+    let tmuxrs = |dir: &Path| {
+        let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+        cmd.env("HOME", &home_path)
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TMUXRS_CONFIG_DIR")
+            .current_dir(dir)
+            .arg("debug")
+            .assert()
+    };
+
+    // The config is found from anywhere below its root, whatever its file is called
+    tmuxrs(&web.join("src"))
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "tmux \\\n  new-session -d -s acme-web -c {} \\;",
+            web.display()
+        )));
+
+    std::fs::write(
+        config_dir.join("acme-web-ops.yml"),
+        "name: acme-web-ops\nroot: ~/work/acme/web\nwindows:\n  - logs: tail -f log\n",
+    )
+    .unwrap();
+    tmuxrs(&web)
+        .failure()
+        .stderr(predicate::str::contains("AmbiguousConfig"))
+        .stderr(predicate::str::contains("acme-web-ops"));
+}
+
+#[test]
+fn test_debug_without_name_ignores_catch_all_roots() {
+    let home = tempfile::TempDir::new().unwrap();
+    let home_path = std::fs::canonicalize(home.path()).unwrap();
+    let notes = home_path.join("notes");
+    std::fs::create_dir_all(&notes).unwrap();
+    let config_dir = home_path.join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("home.yml"),
+        "name: home\nroot: ~\nwindows:\n  - shell: ''\n",
+    )
+    .unwrap();
+    std::fs::write(
+        config_dir.join("everything.yml"),
+        "name: everything\nroot: /\nwindows:\n  - shell: ''\n",
+    )
+    .unwrap();
+    std::fs::write(
+        config_dir.join("notes.yml"),
+        "name: notes\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+
+    let tmuxrs = |dir: &Path| {
+        let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+        cmd.env("HOME", &home_path)
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TMUXRS_CONFIG_DIR")
+            .current_dir(dir)
+            .arg("debug")
+            .assert()
+    };
+
+    // Configs rooted at the home directory or `/` do not shadow the project's own config
+    tmuxrs(&notes).success().stdout(predicate::str::starts_with(
+        "tmux \\\n  new-session -d -s notes ",
+    ));
+
+    // Nor do they claim directories that have no config
+    std::fs::remove_file(config_dir.join("notes.yml")).unwrap();
+    tmuxrs(&notes)
+        .failure()
+        .stderr(predicate::str::contains("tmuxrs/notes.yml"));
+}
+
 /// Core command integration tests
 #[test]
 fn test_start_command_with_explicit_name() {
@@ -126,6 +483,56 @@ windows:
     // No manual cleanup needed - TmuxTestSession's Drop trait handles it
 }
 
+#[test]
+fn test_list_marks_running_sessions() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let home = tempfile::TempDir::new().unwrap();
+    let config_dir = home.path().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    for name in ["listed-running", "listed-idle"] {
+        std::fs::write(
+            config_dir.join(format!("{name}.yml")),
+            format!("name: {name}\nroot: /tmp\nwindows:\n  - shell: ''\n"),
+        )
+        .unwrap();
+    }
+    // The session is named after the file, whatever the `name` field says
+    std::fs::write(
+        config_dir.join("listed-renamed.yml"),
+        "name: Listed Project\nroot: /tmp\nwindows:\n  - shell: ''\n",
+    )
+    .unwrap();
+
+    // A private tmux server, found through TMUX_TMPDIR
+    let tmuxrs = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("tmuxrs").unwrap();
+        cmd.env("HOME", home.path())
+            .env("TMUX_TMPDIR", home.path())
+            .env_remove("TMUX")
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TMUXRS_CONFIG_DIR")
+            .args(args)
+            .assert()
+            .success()
+    };
+    tmuxrs(&["start", "listed-running", "--no-attach"]);
+    tmuxrs(&["start", "listed-renamed", "--no-attach"]);
+    tmuxrs(&["list"])
+        .stdout(predicate::str::contains(
+            "listed-running - /tmp (1 windows) [running]\n",
+        ))
+        .stdout(predicate::str::contains(
+            "Listed Project - /tmp (1 windows) [running]\n",
+        ))
+        .stdout(predicate::str::contains("listed-idle - /tmp (1 windows)\n"));
+    tmuxrs(&["stop", "listed-running"]);
+    tmuxrs(&["stop", "listed-renamed"]);
+}
+
 #[test]
 fn test_start_command_with_directory_detection() {
     if !should_run_integration_tests() {
@@ -213,7 +620,10 @@ windows:
     assert_eq!(configs_list.len(), 3, "Should find 3 configurations");
 
     // Verify all expected configs are found
-    let config_names: Vec<&str> = configs_list.iter().map(|c| c.name.as_str()).collect();
+    let config_names: Vec<&str> = configs_list
+        .iter()
+        .map(|c| c.config.as_ref().unwrap().name.as_str())
+        .collect();
     assert!(config_names.contains(&"web-app"));
     assert!(config_names.contains(&"api-server"));
     assert!(config_names.contains(&"data-pipeline"));
@@ -1,5 +1,11 @@
 use crate::common::{should_run_integration_tests, TmuxTestSession};
-use tmuxrs::session::SessionManager;
+use std::path::{Path, PathBuf};
+use tmuxrs::backend::TmuxBackend;
+use tmuxrs::config::{Config, WindowConfig};
+use tmuxrs::error::TmuxrsError;
+use tmuxrs::fake::FakeBackend;
+use tmuxrs::layout::LayoutError;
+use tmuxrs::session::{ConfigSummary, SessionManager};
 use tmuxrs::tmux::TmuxCommand;
 
 /// Tests for session creation and existence checking
@@ -569,3 +575,918 @@ windows:
         "Complex session should not exist after stopping"
     );
 }
+
+#[test]
+fn test_start_with_append_adds_missing_windows() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("append-windows");
+    let config_dir = session.temp_dir().unwrap().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    // Start a base session by hand with a window that the config also defines
+    session.create().unwrap();
+    session.create_window("editor").unwrap();
+
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: /tmp
+windows:
+  - editor: vim
+  - server: echo "server"
+  - logs:
+      layout: even-horizontal
+      panes:
+        - echo "left"
+        - echo "right"
+"#,
+        session.name()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        true,  // append = true
+    );
+    assert!(result.is_ok(), "Append should succeed: {result:?}");
+
+    let msg = result.unwrap();
+    assert!(
+        msg.contains("added: server, logs"),
+        "Should report added windows: {msg}"
+    );
+    assert!(
+        msg.contains("skipped existing window(s): editor"),
+        "Should report skipped windows: {msg}"
+    );
+
+    // The existing editor window must not be duplicated
+    let windows =
+        TmuxCommand::list_window_names_with_socket(session.name(), Some(session.socket_path()))
+            .unwrap();
+    assert_eq!(windows.iter().filter(|w| *w == "editor").count(), 1);
+    assert!(windows.contains(&"server".to_string()));
+    assert!(windows.contains(&"logs".to_string()));
+
+    // Appending the same config again adds nothing
+    let second = session_manager
+        .start_session_with_options(Some(session.name()), Some(&config_dir), false, true)
+        .unwrap();
+    assert!(
+        second.contains("Appended 0 window(s)"),
+        "Second append should be a no-op: {second}"
+    );
+}
+
+#[test]
+fn test_lifecycle_hooks_run_at_start_and_stop() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("lifecycle-hooks");
+    let temp_dir = session.temp_dir().unwrap();
+    let config_dir = temp_dir.join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    // Each hook appends its name to a log file in the project root
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: {}
+on_project_start: echo start >> hooks.log
+on_project_first_start:
+  - echo first_start >> hooks.log
+on_project_restart: echo restart >> hooks.log
+on_project_stop: echo stop >> hooks.log
+windows:
+  - main: echo "hooks"
+"#,
+        session.name(),
+        temp_dir.display()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    session_manager
+        .start_session_with_options(Some(session.name()), Some(&config_dir), false, false)
+        .unwrap();
+    session_manager
+        .start_session_with_options(Some(session.name()), Some(&config_dir), false, false)
+        .unwrap();
+    session_manager
+        .stop_session_with_options(session.name(), Some(&config_dir))
+        .unwrap();
+
+    let log = std::fs::read_to_string(temp_dir.join("hooks.log")).unwrap();
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        vec!["start", "first_start", "start", "restart", "stop"]
+    );
+}
+
+#[test]
+fn test_failing_first_start_hook_prevents_session() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("failing-hook");
+    let config_dir = session.temp_dir().unwrap().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: /tmp
+on_project_first_start: exit 2
+windows:
+  - main: echo "never"
+"#,
+        session.name()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+
+    let error = result.unwrap_err();
+    assert!(
+        error.to_string().contains("on_project_first_start"),
+        "Error should name the failing hook: {error}"
+    );
+    assert!(!session.exists().unwrap(), "Session should not be created");
+}
+
+/// Session tests against the in-memory backend, no tmux server required
+#[test]
+fn test_lifecycle_hooks_are_recorded_with_fake_backend() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path().join("project");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(
+        temp_dir.path().join("hooked.yml"),
+        format!(
+            r#"
+name: hooked
+root: {}
+on_project_start: touch started
+on_project_first_start:
+  - touch first-started
+on_project_stop: touch stopped
+windows:
+  - main: ''
+"#,
+            root.display()
+        ),
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("hooked"), Some(temp_dir.path()), false, false)
+        .unwrap();
+    manager
+        .stop_session_with_options("hooked", Some(temp_dir.path()))
+        .unwrap();
+
+    let hooks: Vec<(String, String)> = manager
+        .backend()
+        .lifecycle_hooks()
+        .into_iter()
+        .map(|hook| {
+            assert_eq!(hook.working_dir, root);
+            (hook.hook, hook.command)
+        })
+        .collect();
+    assert_eq!(
+        hooks,
+        [
+            ("on_project_start", "touch started"),
+            ("on_project_first_start", "touch first-started"),
+            ("on_project_stop", "touch stopped"),
+        ]
+        .map(|(hook, command)| (hook.to_string(), command.to_string()))
+    );
+    // Nothing was run for real
+    assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+}
+
+#[test]
+fn test_start_session_with_fake_backend() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("faked.yml"),
+        r#"
+name: faked
+root: /srv/faked
+pre_window: nvm use
+startup_window: editor
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - command: cargo test
+          root: tests
+  - server: cargo run
+"#,
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    let result = manager
+        .start_session_with_options(Some("faked"), Some(temp_dir.path()), false, false)
+        .unwrap();
+    assert_eq!(result, "Started detached session 'faked'");
+
+    let session = manager.backend().session("faked").unwrap();
+    assert_eq!(session.working_dir, PathBuf::from("/srv/faked"));
+    assert_eq!(session.window_names(), vec!["editor", "server"]);
+    assert_eq!(session.active_window, 0);
+
+    let editor = session.window("editor").unwrap();
+    assert_eq!(editor.layout.as_deref(), Some("main-vertical"));
+    assert_eq!(editor.panes[0].sent_keys, vec!["nvm use", "vim"]);
+    assert_eq!(
+        editor.panes[1].working_dir,
+        PathBuf::from("/srv/faked/tests")
+    );
+    assert_eq!(editor.panes[1].sent_keys, vec!["nvm use", "cargo test"]);
+
+    let server = session.window("server").unwrap();
+    assert_eq!(server.panes[0].sent_keys, vec!["nvm use", "cargo run"]);
+}
+
+#[test]
+fn test_startup_window_with_repeated_and_numeric_names() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("startup.yml"),
+        r#"
+name: startup
+root: /srv/startup
+startup_window: "2"
+startup_pane: 1
+windows:
+  - vim: vim
+  - "2":
+      panes:
+        - htop
+        - tail -f log
+  - vim: vim notes
+  - shell: ''
+"#,
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("startup"), Some(temp_dir.path()), false, false)
+        .unwrap();
+
+    // The window named "2" sits at index 1, and the repeated name is no obstacle
+    let session = manager.backend().session("startup").unwrap();
+    assert_eq!(session.window_names(), vec!["vim", "2", "vim", "shell"]);
+    assert_eq!(session.active_window, 1);
+    assert_eq!(session.windows[1].active_pane, 1);
+
+    // A repeated name selects its first window
+    std::fs::write(
+        temp_dir.path().join("startup.yml"),
+        "name: startup\nroot: /srv/startup\nstartup_window: vim\nwindows:\n  - shell: ''\n  - vim: vim\n  - vim: vim notes\n",
+    )
+    .unwrap();
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("startup"), Some(temp_dir.path()), false, false)
+        .unwrap();
+    assert_eq!(
+        manager.backend().session("startup").unwrap().active_window,
+        1
+    );
+}
+
+#[test]
+fn test_append_and_stop_with_fake_backend() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("faked.yml"),
+        "name: faked\nroot: /tmp\nwindows:\n  - editor: vim\n  - logs: tail -f log\n",
+    )
+    .unwrap();
+
+    let backend = FakeBackend::new();
+    backend
+        .execute(TmuxCommand::new_session_command("faked", Path::new("/tmp")))
+        .unwrap();
+    backend
+        .execute(TmuxCommand::rename_window_command("faked", "^", "editor"))
+        .unwrap();
+
+    let manager = SessionManager::with_backend(backend);
+    let result = manager
+        .start_session_with_options(Some("faked"), Some(temp_dir.path()), false, true)
+        .unwrap();
+    assert_eq!(
+        result,
+        "Appended 1 window(s) to session 'faked' (added: logs); skipped existing window(s): editor"
+    );
+    assert_eq!(
+        manager.backend().session("faked").unwrap().window_names(),
+        vec!["editor", "logs"]
+    );
+
+    manager
+        .stop_session_with_options("faked", Some(temp_dir.path()))
+        .unwrap();
+    assert!(manager.backend().sessions().is_empty());
+    assert!(manager
+        .stop_session_with_options("faked", Some(temp_dir.path()))
+        .is_err());
+}
+
+#[test]
+fn test_append_targets_new_windows_by_index() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("indexed.yml"),
+        r#"
+name: indexed
+root: /tmp
+windows:
+  - editor: vim
+  - "2":
+      panes:
+        - htop
+        - tail -f log
+  - api.v2: cargo run
+"#,
+    )
+    .unwrap();
+
+    // Windows 0 and 3 leave gaps that tmux would fill without an explicit index
+    let backend = FakeBackend::new();
+    backend
+        .execute(TmuxCommand::new_session_command(
+            "indexed",
+            Path::new("/tmp"),
+        ))
+        .unwrap();
+    backend
+        .execute(TmuxCommand::rename_window_command("indexed", "^", "editor"))
+        .unwrap();
+    backend
+        .execute(TmuxCommand::new_window_command(
+            "indexed:3",
+            "notes",
+            None,
+            None,
+        ))
+        .unwrap();
+
+    let manager = SessionManager::with_backend(backend);
+    manager
+        .start_session_with_options(Some("indexed"), Some(temp_dir.path()), false, true)
+        .unwrap();
+
+    let session = manager.backend().session("indexed").unwrap();
+    assert_eq!(
+        session.window_names(),
+        vec!["editor", "notes", "2", "api.v2"]
+    );
+    let window = |name: &str| session.window(name).unwrap();
+    assert_eq!((window("2").index, window("api.v2").index), (4, 5));
+    assert_eq!(window("2").panes[0].sent_keys, vec!["htop"]);
+    assert_eq!(window("2").panes[1].sent_keys, vec!["tail -f log"]);
+    assert_eq!(window("api.v2").panes[0].sent_keys, vec!["cargo run"]);
+}
+
+#[test]
+fn test_invalid_layout_rejected_before_session_is_created() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("laid-out.yml"),
+        r#"
+name: laid-out
+root: /tmp
+windows:
+  - editor:
+      layout: "6d71,160x48,0,0{80x48,0,0,0,79x48,81,0[79x23,81,0,1,79x24,81,24,2]}"
+      panes:
+        - vim
+        - cargo watch
+"#,
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    let result =
+        manager.start_session_with_options(Some("laid-out"), Some(temp_dir.path()), false, false);
+
+    match result {
+        Err(TmuxrsError::InvalidLayout { window, source }) => {
+            assert_eq!(window, "editor");
+            assert_eq!(
+                source,
+                LayoutError::PaneCountMismatch {
+                    layout_panes: 3,
+                    configured_panes: 2
+                }
+            );
+        }
+        other => panic!("Expected InvalidLayout error, got {other:?}"),
+    }
+    assert!(manager.backend().sessions().is_empty());
+}
+
+#[test]
+fn test_freeze_session_with_fake_backend() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("faked.yml"),
+        r#"
+name: faked
+root: /srv/faked
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - command: cargo test
+          root: tests
+  - server: cargo run
+"#,
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("faked"), Some(temp_dir.path()), false, false)
+        .unwrap();
+
+    let frozen_dir = temp_dir.path().join("frozen");
+    let result = manager
+        .freeze_session("faked", Some(&frozen_dir), false, true)
+        .unwrap();
+    let frozen_file = frozen_dir.join("faked.yml");
+    assert_eq!(
+        result,
+        format!("Saved session 'faked' to {}", frozen_file.display())
+    );
+
+    // The frozen config loads back and describes the same windows
+    let config = Config::parse_file(&frozen_file).unwrap();
+    assert_eq!(config.name, "faked");
+    assert_eq!(config.root.as_deref(), Some("/srv/faked"));
+    match &config.windows[0] {
+        WindowConfig::WithLayout { window } => {
+            let editor = &window["editor"];
+            assert_eq!(editor.layout.as_deref(), Some("main-vertical"));
+            // Each pane runs the program it was running, with its arguments
+            assert_eq!(editor.panes[0].command(), "vim");
+            assert_eq!(editor.panes[1].command(), "cargo test");
+            assert_eq!(editor.panes[1].root(), Some("tests"));
+        }
+        other => panic!("Expected WithLayout window, got {other:?}"),
+    }
+    match &config.windows[1] {
+        WindowConfig::Complex { window } => assert_eq!(window["server"], "cargo run"),
+        other => panic!("Expected Complex window, got {other:?}"),
+    }
+    let frozen = std::fs::read_to_string(&frozen_file).unwrap();
+    assert!(
+        frozen.contains("#   editor, pane 1: cargo test\n"),
+        "{frozen}"
+    );
+    assert!(
+        frozen.contains("#   server, pane 0: cargo run\n"),
+        "{frozen}"
+    );
+
+    // An existing config is only replaced with force
+    assert!(matches!(
+        manager.freeze_session("faked", Some(&frozen_dir), false, true),
+        Err(TmuxrsError::ConfigExists(_))
+    ));
+    assert!(manager
+        .freeze_session("missing", Some(&frozen_dir), true, true)
+        .is_err());
+
+    // Without commands, the programs are only listed in the comment
+    manager
+        .freeze_session("faked", Some(&frozen_dir), true, false)
+        .unwrap();
+    let config = Config::parse_file(&frozen_file).unwrap();
+    match &config.windows[1] {
+        WindowConfig::Complex { window } => assert_eq!(window["server"], ""),
+        other => panic!("Expected Complex window, got {other:?}"),
+    }
+    let frozen = std::fs::read_to_string(&frozen_file).unwrap();
+    assert!(
+        frozen.contains("#   server, pane 0: cargo run\n"),
+        "{frozen}"
+    );
+}
+
+#[test]
+fn test_save_and_restore_with_fake_backend() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("faked.yml"),
+        r#"
+name: faked
+root: /srv/faked
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - command: cargo test
+          root: tests
+  - server: cargo run
+"#,
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("faked"), Some(temp_dir.path()), false, false)
+        .unwrap();
+    // Sessions without a config are not managed by tmuxrs and are not saved
+    manager
+        .backend()
+        .execute(TmuxCommand::new_session_command(
+            "scratch",
+            Path::new("/tmp"),
+        ))
+        .unwrap();
+
+    let state_file = temp_dir.path().join("state").join("sessions.json");
+    let result = manager
+        .save_sessions(Some(temp_dir.path()), Some(&state_file), true)
+        .unwrap();
+    assert_eq!(
+        result,
+        format!("Saved 1 session(s) to {}: faked", state_file.display())
+    );
+
+    // Restore onto a fresh server
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    let result = manager.restore_sessions(Some(&state_file), true).unwrap();
+    assert_eq!(result, "Restored 1 session(s): faked");
+
+    let session = manager.backend().session("faked").unwrap();
+    assert_eq!(session.working_dir, PathBuf::from("/srv/faked"));
+    assert_eq!(session.window_names(), vec!["editor", "server"]);
+    let editor = session.window("editor").unwrap();
+    assert_eq!(editor.layout.as_deref(), Some("main-vertical"));
+    assert_eq!(
+        editor.panes[1].working_dir,
+        PathBuf::from("/srv/faked/tests")
+    );
+
+    // Each pane prints its saved scrollback, then runs its program again
+    // with the arguments it was started with
+    let scrollback_file = state_file
+        .with_extension("scrollback")
+        .join("faked/0.1.txt");
+    assert_eq!(
+        std::fs::read_to_string(&scrollback_file).unwrap(),
+        "cargo test\n"
+    );
+    assert_eq!(
+        editor.panes[1].sent_keys,
+        vec![format!(
+            "clear; cat -- {}; cargo test",
+            scrollback_file.display()
+        )]
+    );
+    assert_eq!(
+        session.window("server").unwrap().panes[0].sent_keys,
+        vec![format!(
+            "clear; cat -- {}; cargo run",
+            state_file
+                .with_extension("scrollback")
+                .join("faked/1.0.txt")
+                .display()
+        )]
+    );
+
+    let result = manager.restore_sessions(Some(&state_file), true).unwrap();
+    assert_eq!(
+        result,
+        "Restored 0 session(s); skipped running session(s): faked"
+    );
+
+    // Without commands, the programs are listed instead
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    let result = manager.restore_sessions(Some(&state_file), false).unwrap();
+    assert_eq!(
+        result,
+        "Restored 1 session(s): faked\n\
+         Programs that were running, to start again by hand:\n  \
+         faked: editor, pane 0: vim\n  \
+         faked: editor, pane 1: cargo test\n  \
+         faked: server, pane 0: cargo run"
+    );
+    let session = manager.backend().session("faked").unwrap();
+    assert_eq!(
+        session.window("editor").unwrap().panes[1].sent_keys,
+        vec![format!("clear; cat -- {}", scrollback_file.display())]
+    );
+}
+
+#[test]
+fn test_restore_windows_with_repeated_and_numeric_names() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("adhoc.yml"),
+        "name: adhoc\nroot: /srv/adhoc\nwindows:\n  - shell: ''\n",
+    )
+    .unwrap();
+
+    // An ad-hoc session whose windows kept their automatic names
+    let backend = FakeBackend::new();
+    let run = |args: &[&str]| {
+        let command = args
+            .iter()
+            .fold(TmuxCommand::new(), |command, arg| command.arg(*arg));
+        backend.execute(command).unwrap();
+    };
+    run(&[
+        "new-session",
+        "-d",
+        "-s",
+        "adhoc",
+        "-n",
+        "vim",
+        "-c",
+        "/srv/adhoc",
+    ]);
+    run(&["send-keys", "-t", "adhoc:0", "vim", "Enter"]);
+    run(&[
+        "new-window",
+        "-t",
+        "adhoc",
+        "-n",
+        "vim",
+        "-c",
+        "/srv/adhoc/docs",
+    ]);
+    run(&["send-keys", "-t", "adhoc:1", "vim", "Enter"]);
+    run(&[
+        "new-window",
+        "-t",
+        "adhoc",
+        "-n",
+        "0",
+        "-c",
+        "/srv/adhoc/logs",
+    ]);
+    run(&["send-keys", "-t", "adhoc:2", "tail", "Enter"]);
+    let manager = SessionManager::with_backend(backend);
+
+    let state_file = temp_dir.path().join("sessions.json");
+    manager
+        .save_sessions(Some(temp_dir.path()), Some(&state_file), false)
+        .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    assert_eq!(
+        manager.restore_sessions(Some(&state_file), false).unwrap(),
+        "Restored 1 session(s): adhoc\n\
+         Programs that were running, to start again by hand:\n  \
+         adhoc: vim, pane 0: vim\n  \
+         adhoc: vim-2, pane 0: vim\n  \
+         adhoc: 0, pane 0: tail"
+    );
+
+    // Every window is rebuilt in place, including the one named like an index
+    let session = manager.backend().session("adhoc").unwrap();
+    let windows: Vec<(&str, &Path)> = session
+        .windows
+        .iter()
+        .map(|window| (window.name.as_str(), window.panes[0].working_dir.as_path()))
+        .collect();
+    assert_eq!(
+        windows,
+        vec![
+            ("vim", Path::new("/srv/adhoc")),
+            ("vim-2", Path::new("/srv/adhoc/docs")),
+            ("0", Path::new("/srv/adhoc/logs")),
+        ]
+    );
+}
+
+#[test]
+fn test_copy_delete_and_rename_configs() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config_dir = Some(temp_dir.path());
+    std::fs::write(
+        temp_dir.path().join("web.yml"),
+        "# The website\nname: web\nroot: /srv/web\nwindows:\n  - server: npm start\n",
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .copy_config("web", "api", config_dir, false)
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(temp_dir.path().join("api.yml")).unwrap(),
+        "# The website\nname: api\nroot: /srv/web\nwindows:\n  - server: npm start\n"
+    );
+    assert!(matches!(
+        manager.copy_config("web", "api", config_dir, false),
+        Err(TmuxrsError::ConfigExists(_))
+    ));
+
+    manager
+        .rename_config("api", "site", config_dir, false)
+        .unwrap();
+    assert!(!temp_dir.path().join("api.yml").exists());
+    let config = Config::parse_file(&temp_dir.path().join("site.yml")).unwrap();
+    assert_eq!(config.name, "site");
+
+    manager.delete_config("site", config_dir, false).unwrap();
+    assert!(!temp_dir.path().join("site.yml").exists());
+    assert!(matches!(
+        manager.delete_config("site", config_dir, false),
+        Err(TmuxrsError::ConfigNotFound(_))
+    ));
+}
+
+#[test]
+fn test_config_of_running_session_is_left_alone_unless_forced() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config_dir = Some(temp_dir.path());
+    for name in ["web", "api"] {
+        std::fs::write(
+            temp_dir.path().join(format!("{name}.yml")),
+            format!("name: {name}\nroot: /tmp\nwindows:\n  - shell: ''\n"),
+        )
+        .unwrap();
+    }
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    manager
+        .start_session_with_options(Some("web"), config_dir, false, false)
+        .unwrap();
+
+    match manager.delete_config("web", config_dir, false) {
+        Err(TmuxrsError::SessionRunning(name)) => assert_eq!(name, "web"),
+        other => panic!("Expected SessionRunning error, got {other:?}"),
+    }
+    assert!(matches!(
+        manager.rename_config("web", "site", config_dir, false),
+        Err(TmuxrsError::SessionRunning(_))
+    ));
+    assert!(temp_dir.path().join("web.yml").exists());
+
+    manager
+        .rename_config("web", "site", config_dir, true)
+        .unwrap();
+    assert!(!temp_dir.path().join("web.yml").exists());
+    assert!(temp_dir.path().join("site.yml").exists());
+}
+
+#[test]
+fn test_summarize_configs_reports_running_sessions() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    for name in ["api", "web"] {
+        std::fs::write(
+            temp_dir.path().join(format!("{name}.yml")),
+            format!("name: {name}\nroot: /srv/{name}\nwindows:\n  - editor: vim\n  - shell: ''\n"),
+        )
+        .unwrap();
+    }
+    std::fs::write(temp_dir.path().join("broken.yml"), "name: [broken").unwrap();
+    // Sessions are named after the file, so only the first of these is running
+    std::fs::write(
+        temp_dir.path().join("docs.yml"),
+        "name: Documentation\nroot: /srv/docs\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join("handbook.yml"),
+        "name: web\nroot: /srv/handbook\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+
+    // Shadowed by web.yml, so not the config of the running session
+    std::fs::write(
+        temp_dir.path().join("web.yaml"),
+        "name: web\nroot: /srv/old-web\nwindows:\n  - editor: vim\n",
+    )
+    .unwrap();
+
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    for name in ["web", "docs"] {
+        manager
+            .start_session_with_options(Some(name), Some(temp_dir.path()), false, false)
+            .unwrap();
+    }
+
+    let files = manager.list_configs(Some(temp_dir.path())).unwrap();
+    let before = manager.backend().history().len();
+    let summaries = manager.summarize_configs(&files).unwrap();
+    assert_eq!(
+        manager.backend().history().len(),
+        before + 1,
+        "one list-sessions call"
+    );
+
+    assert_eq!(
+        summaries,
+        vec![
+            ConfigSummary {
+                name: "api".to_string(),
+                root: "/srv/api".into(),
+                windows: 2,
+                config_file: temp_dir.path().join("api.yml"),
+                source_dir: temp_dir.path().to_path_buf(),
+                shadowed_by: None,
+                running: false,
+            },
+            ConfigSummary {
+                name: "Documentation".to_string(),
+                root: "/srv/docs".into(),
+                windows: 1,
+                config_file: temp_dir.path().join("docs.yml"),
+                source_dir: temp_dir.path().to_path_buf(),
+                shadowed_by: None,
+                running: true,
+            },
+            ConfigSummary {
+                name: "web".to_string(),
+                root: "/srv/handbook".into(),
+                windows: 1,
+                config_file: temp_dir.path().join("handbook.yml"),
+                source_dir: temp_dir.path().to_path_buf(),
+                shadowed_by: None,
+                running: false,
+            },
+            ConfigSummary {
+                name: "web".to_string(),
+                root: "/srv/web".into(),
+                windows: 2,
+                config_file: temp_dir.path().join("web.yml"),
+                source_dir: temp_dir.path().to_path_buf(),
+                shadowed_by: None,
+                running: true,
+            },
+            ConfigSummary {
+                name: "web".to_string(),
+                root: "/srv/old-web".into(),
+                windows: 1,
+                config_file: temp_dir.path().join("web.yaml"),
+                source_dir: temp_dir.path().to_path_buf(),
+                shadowed_by: Some(temp_dir.path().join("web.yml")),
+                running: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_list_sessions_with_fake_backend() {
+    let manager = SessionManager::with_backend(FakeBackend::new());
+    assert!(manager.list_sessions().unwrap().is_empty());
+
+    for name in ["web", "api"] {
+        manager
+            .backend()
+            .execute(TmuxCommand::new_session_command(name, Path::new("/tmp")))
+            .unwrap();
+    }
+    manager
+        .backend()
+        .execute(TmuxCommand::new().arg("new-window").arg("-t").arg("api"))
+        .unwrap();
+
+    let sessions = manager.list_sessions().unwrap();
+    let summary: Vec<(&str, usize, usize)> = sessions
+        .iter()
+        .map(|session| (session.name.as_str(), session.attached, session.windows))
+        .collect();
+    assert_eq!(summary, vec![("web", 0, 1), ("api", 0, 2)]);
+    assert!(sessions[0].created < sessions[1].created);
+}
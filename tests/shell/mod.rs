@@ -622,3 +622,75 @@ windows:
 
     // Session cleanup happens automatically via TmuxTestSession::Drop
 }
+
+#[test]
+fn test_pre_window_and_window_pre_sent_to_every_pane() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("pre-window");
+    let config_dir = session.temp_dir().unwrap().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: /tmp
+pre_window: export TMUXRS_PRE=project
+windows:
+  - single: echo "single-command"
+  - split:
+      pre: export TMUXRS_WIN=window
+      panes:
+        - echo "pane-zero"
+        - echo "pane-one"
+"#,
+        session.name()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+    assert!(result.is_ok(), "Failed to start session: {result:?}");
+
+    // Give the shells time to echo the keys they received
+    thread::sleep(Duration::from_millis(1000));
+
+    let capture = |target: &str| {
+        TmuxCommand::with_socket(session.socket_path())
+            .arg("capture-pane")
+            .arg("-p")
+            .arg("-t")
+            .arg(format!("{}:{target}", session.name()))
+            .execute()
+            .unwrap()
+    };
+    let position = |output: &str, needle: &str| {
+        output
+            .find(needle)
+            .unwrap_or_else(|| panic!("'{needle}' not sent to pane:\n{output}"))
+    };
+
+    // pre_window is sent before the window command
+    let single = capture("single");
+    assert!(position(&single, "TMUXRS_PRE=project") < position(&single, "single-command"));
+
+    // Every split pane gets pre_window, then the window's pre, then its own command
+    for (pane, command) in [("split.0", "pane-zero"), ("split.1", "pane-one")] {
+        let output = capture(pane);
+        let pre_window = position(&output, "TMUXRS_PRE=project");
+        let pre = position(&output, "TMUXRS_WIN=window");
+        assert!(
+            pre_window < pre && pre < position(&output, command),
+            "Commands sent out of order in {pane}:\n{output}"
+        );
+    }
+}
@@ -410,3 +410,248 @@ fn test_tmux_select_layout() {
         "Should still have 3 panes after layout changes"
     );
 }
+
+#[test]
+fn test_windows_created_in_config_order() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("window-order");
+    let config_dir = session.temp_dir().unwrap().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    // A single mapping with several keys, followed by more list entries
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: /tmp
+windows:
+  - editor: echo "editor"
+    server: echo "server"
+    logs: echo "logs"
+    console: echo "console"
+  - zeta:
+      panes:
+        - echo "zeta"
+    alpha:
+      panes:
+        - echo "alpha"
+  - misc: echo "misc"
+"#,
+        session.name()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+    assert!(result.is_ok(), "Failed to start session: {result:?}");
+
+    // list-windows reports windows in index order
+    let windows =
+        TmuxCommand::list_window_names_with_socket(session.name(), Some(session.socket_path()))
+            .unwrap();
+    assert_eq!(
+        windows,
+        vec!["editor", "server", "logs", "console", "zeta", "alpha", "misc"],
+        "Windows should be created in file order"
+    );
+}
+
+#[test]
+fn test_window_and_pane_root_overrides() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("root-overrides");
+    let temp_dir = session.temp_dir().unwrap().canonicalize().unwrap();
+    let config_dir = temp_dir.join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    for dir in ["frontend", "backend", "backend/migrations"] {
+        std::fs::create_dir_all(temp_dir.join(dir)).unwrap();
+    }
+
+    // The first window is the session's initial window, so it must be moved too
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: {}
+windows:
+  - frontend:
+      root: frontend
+      panes:
+        - echo "frontend"
+  - backend:
+      root: backend
+      panes:
+        - echo "backend"
+        - command: echo "migrations"
+          root: backend/migrations
+        - root: /tmp
+  - shell: echo "session root"
+"#,
+        session.name(),
+        temp_dir.display()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+    assert!(result.is_ok(), "Failed to start session: {result:?}");
+
+    let pane_paths = |window: &str| -> Vec<String> {
+        TmuxCommand::with_socket(session.socket_path())
+            .arg("list-panes")
+            .arg("-t")
+            .arg(format!("{}:{window}", session.name()))
+            .arg("-F")
+            .arg("#{pane_current_path}")
+            .execute()
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+    let path = |relative: &str| temp_dir.join(relative).display().to_string();
+
+    assert_eq!(pane_paths("frontend"), vec![path("frontend")]);
+    assert_eq!(
+        pane_paths("backend"),
+        vec![
+            path("backend"),
+            path("backend/migrations"),
+            "/tmp".to_string()
+        ]
+    );
+    assert_eq!(pane_paths("shell"), vec![temp_dir.display().to_string()]);
+}
+
+#[test]
+fn test_pane_split_direction_size_and_target() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("pane-splits");
+    let temp_dir = session.temp_dir().unwrap().canonicalize().unwrap();
+    let config_dir = temp_dir.join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    for dir in ["a", "b", "c"] {
+        std::fs::create_dir_all(temp_dir.join(dir)).unwrap();
+    }
+
+    // Pane c splits pane a, so tmux numbers it between a and b
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: {}
+windows:
+  - main:
+      panes:
+        - root: a
+        - root: b
+          split: vertical
+          size: 5
+        - root: c
+          target: 0
+          size: 40%
+"#,
+        session.name(),
+        temp_dir.display()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+    assert!(result.is_ok(), "Failed to start session: {result:?}");
+
+    let panes: Vec<String> = TmuxCommand::with_socket(session.socket_path())
+        .arg("list-panes")
+        .arg("-t")
+        .arg(format!("{}:main", session.name()))
+        .arg("-F")
+        .arg("#{pane_current_path} #{pane_height}")
+        .execute()
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let path = |relative: &str| temp_dir.join(relative).display().to_string();
+
+    assert_eq!(panes.len(), 3);
+    assert!(panes[0].starts_with(&path("a")), "{panes:?}");
+    assert!(panes[1].starts_with(&path("c")), "{panes:?}");
+    assert_eq!(panes[2], format!("{} 5", path("b")));
+}
+
+#[test]
+fn test_startup_window_and_pane_selected() {
+    if !should_run_integration_tests() {
+        eprintln!("Skipping integration test - use 'docker compose run --rm integration-tests' or set INTEGRATION_TESTS=1");
+        return;
+    }
+
+    let session = TmuxTestSession::with_temp_dir("startup-window");
+    let config_dir = session.temp_dir().unwrap().join(".config").join("tmuxrs");
+    std::fs::create_dir_all(&config_dir).unwrap();
+
+    let config_file = config_dir.join(format!("{}.yml", session.name()));
+    let yaml_content = format!(
+        r#"
+name: {}
+root: /tmp
+startup_window: editor
+startup_pane: 1
+windows:
+  - editor:
+      panes:
+        - echo "vim"
+        - echo "tests"
+  - server: echo "server"
+  - logs: echo "logs"
+"#,
+        session.name()
+    );
+    std::fs::write(&config_file, yaml_content).unwrap();
+
+    let session_manager = SessionManager::with_socket(session.socket_path());
+    let result = session_manager.start_session_with_options(
+        Some(session.name()),
+        Some(&config_dir),
+        false, // attach = false
+        false, // append = false
+    );
+    assert!(result.is_ok(), "Failed to start session: {result:?}");
+
+    let active = TmuxCommand::with_socket(session.socket_path())
+        .arg("display-message")
+        .arg("-p")
+        .arg("-t")
+        .arg(session.name())
+        .arg("#{window_name}.#{pane_index}")
+        .execute()
+        .unwrap();
+    assert_eq!(active.trim(), "editor.1");
+}